@@ -0,0 +1,458 @@
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, Result};
+use image::{imageops, RgbaImage};
+use paperdoll_tar::paperdoll::{ImageData, PaperdollFactory, Point};
+use serde::Serialize;
+
+use crate::common::sorted_ids;
+
+const ATLAS_MAX_SIZE: u32 = 2048;
+const ATLAS_PADDING: u32 = 2;
+
+#[derive(Serialize)]
+struct AtlasRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Serialize)]
+struct AtlasSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Serialize)]
+struct AtlasPoint {
+    x: f32,
+    y: f32,
+}
+
+impl From<&Point> for AtlasPoint {
+    fn from(point: &Point) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AtlasFrame {
+    filename: String,
+    frame: AtlasRect,
+    rotated: bool,
+    trimmed: bool,
+    sprite_source_size: AtlasRect,
+    source_size: AtlasSize,
+    // normalized as in TexturePacker
+    pivot: AtlasPoint,
+}
+
+#[derive(Serialize)]
+struct AtlasTexture {
+    image: String,
+    format: &'static str,
+    size: AtlasSize,
+    scale: u32,
+    frames: Vec<AtlasFrame>,
+}
+
+#[derive(Serialize)]
+struct AtlasSlot {
+    id: u32,
+    name: String,
+    z: usize,
+    required: bool,
+    width: u32,
+    height: u32,
+    positions: Vec<AtlasPoint>,
+    anchor: AtlasPoint,
+    candidates: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct AtlasDoll {
+    id: u32,
+    name: String,
+    width: u32,
+    height: u32,
+    background: Option<String>,
+    slots: Vec<AtlasSlot>,
+}
+
+#[derive(Serialize)]
+struct AtlasMeta {
+    app: &'static str,
+    version: &'static str,
+    name: String,
+}
+
+#[derive(Serialize)]
+struct Atlas {
+    textures: Vec<AtlasTexture>,
+    dolls: Vec<AtlasDoll>,
+    meta: AtlasMeta,
+}
+
+struct AtlasSprite {
+    name: String,
+    image: RgbaImage,
+    pivot: Point,
+}
+
+struct AtlasPage {
+    width: u32,
+    height: u32,
+    placements: Vec<(usize, u32, u32)>,
+}
+
+// writes `<name>.json` and one `<name>_<page>.png` per atlas page next to it
+pub fn export_atlas<P>(ppd: &PaperdollFactory, include_dolls: bool, path: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref().with_extension("json");
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow!("Invalid atlas path: {}", path.display()))?;
+
+    let mut sprites = vec![];
+
+    if include_dolls {
+        for id in sorted_ids(ppd.dolls().map(|(id, _)| *id)) {
+            if let Some(doll) = ppd.get_doll(id) {
+                if let Some(image) = to_rgba_image(&doll.image, &doll_frame_name(id))? {
+                    sprites.push(AtlasSprite {
+                        name: doll_frame_name(id),
+                        image,
+                        pivot: Point::default(),
+                    });
+                }
+            }
+        }
+    }
+
+    for id in sorted_ids(ppd.fragments().map(|(id, _)| *id)) {
+        if let Some(fragment) = ppd.get_fragment(id) {
+            if let Some(image) = to_rgba_image(&fragment.image, &fragment_frame_name(id))? {
+                sprites.push(AtlasSprite {
+                    name: fragment_frame_name(id),
+                    image,
+                    pivot: fragment.pivot,
+                });
+            }
+        }
+    }
+
+    let pages = pack_sprites(&sprites);
+
+    remove_previous_pages(&path, &stem);
+
+    let mut textures = vec![];
+
+    for (page_index, page) in pages.iter().enumerate() {
+        let image_name = format!("{}_{}.png", stem, page_index);
+
+        let mut image = RgbaImage::new(page.width, page.height);
+
+        let mut frames = vec![];
+
+        for (sprite_index, x, y) in &page.placements {
+            let sprite = &sprites[*sprite_index];
+
+            let (w, h) = sprite.image.dimensions();
+
+            imageops::replace(&mut image, &sprite.image, *x as i64, *y as i64);
+
+            frames.push(AtlasFrame {
+                filename: sprite.name.clone(),
+                frame: AtlasRect { x: *x, y: *y, w, h },
+                rotated: false,
+                trimmed: false,
+                sprite_source_size: AtlasRect { x: 0, y: 0, w, h },
+                source_size: AtlasSize { w, h },
+                pivot: AtlasPoint {
+                    x: sprite.pivot.x / w as f32,
+                    y: sprite.pivot.y / h as f32,
+                },
+            });
+        }
+
+        image.save(path.with_file_name(&image_name))?;
+
+        textures.push(AtlasTexture {
+            image: image_name,
+            format: "RGBA8888",
+            size: AtlasSize {
+                w: page.width,
+                h: page.height,
+            },
+            scale: 1,
+            frames,
+        });
+    }
+
+    let has_frame = |name: &str| sprites.iter().any(|sprite| sprite.name == name);
+
+    let mut dolls = vec![];
+
+    for id in sorted_ids(ppd.dolls().map(|(id, _)| *id)) {
+        let Some(doll) = ppd.get_doll(id) else {
+            continue;
+        };
+
+        let background = doll_frame_name(id);
+
+        let slots = doll
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(z, slot_id)| {
+                let slot = ppd.get_slot(*slot_id)?;
+
+                Some(AtlasSlot {
+                    id: *slot_id,
+                    name: slot.desc.clone(),
+                    z,
+                    required: slot.required,
+                    width: slot.width,
+                    height: slot.height,
+                    positions: slot.positions.iter().map(AtlasPoint::from).collect(),
+                    anchor: AtlasPoint::from(&slot.anchor),
+                    candidates: slot
+                        .candidates
+                        .iter()
+                        .map(|fragment_id| fragment_frame_name(*fragment_id))
+                        .filter(|name| has_frame(name))
+                        .collect(),
+                })
+            })
+            .collect();
+
+        dolls.push(AtlasDoll {
+            id,
+            name: doll.desc.clone(),
+            width: doll.width,
+            height: doll.height,
+            background: has_frame(&background).then_some(background),
+            slots,
+        });
+    }
+
+    let atlas = Atlas {
+        textures,
+        dolls,
+        meta: AtlasMeta {
+            app: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            name: ppd.meta.name.clone(),
+        },
+    };
+
+    fs::write(&path, serde_json::to_string_pretty(&atlas)?)?;
+
+    Ok(())
+}
+
+// a simple shelf packer, sprites are placed from the tallest to the shortest
+fn pack_sprites(sprites: &[AtlasSprite]) -> Vec<AtlasPage> {
+    let mut order: Vec<usize> = (0..sprites.len()).collect();
+
+    // sorting is stable so sprites of the same size keep their id order
+    order.sort_by(|a, b| {
+        let (aw, ah) = sprites[*a].image.dimensions();
+        let (bw, bh) = sprites[*b].image.dimensions();
+
+        bh.cmp(&ah).then(bw.cmp(&aw))
+    });
+
+    let mut pages: Vec<AtlasPage> = vec![];
+
+    // index of the page sprites are currently placed into
+    let mut current = None;
+
+    let mut cursor_x = 0;
+    let mut cursor_y = 0;
+    let mut shelf_height = 0;
+
+    for index in order {
+        let (w, h) = sprites[index].image.dimensions();
+
+        // oversized sprites get a page of their own
+        if w > ATLAS_MAX_SIZE || h > ATLAS_MAX_SIZE {
+            pages.push(AtlasPage {
+                width: w,
+                height: h,
+                placements: vec![(index, 0, 0)],
+            });
+
+            continue;
+        }
+
+        if cursor_x + w > ATLAS_MAX_SIZE {
+            cursor_x = 0;
+            cursor_y += shelf_height;
+            shelf_height = 0;
+        }
+
+        if current.is_none() || cursor_y + h > ATLAS_MAX_SIZE {
+            pages.push(AtlasPage {
+                width: 0,
+                height: 0,
+                placements: vec![],
+            });
+
+            current = Some(pages.len() - 1);
+
+            cursor_x = 0;
+            cursor_y = 0;
+            shelf_height = 0;
+        }
+
+        let Some(page) = current.and_then(|current| pages.get_mut(current)) else {
+            continue;
+        };
+
+        page.placements.push((index, cursor_x, cursor_y));
+        page.width = page.width.max(cursor_x + w);
+        page.height = page.height.max(cursor_y + h);
+
+        cursor_x += w + ATLAS_PADDING;
+        shelf_height = shelf_height.max(h + ATLAS_PADDING);
+    }
+
+    pages
+}
+
+fn doll_frame_name(id: u32) -> String {
+    format!("doll_{}", id)
+}
+
+fn fragment_frame_name(id: u32) -> String {
+    format!("fragment_{}", id)
+}
+
+// pages of an earlier export to the same path would be left behind when there are fewer now
+fn remove_previous_pages(path: &Path, stem: &str) {
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+
+    let Ok(atlas) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return;
+    };
+
+    let Some(textures) = atlas["textures"].as_array() else {
+        return;
+    };
+
+    let prefix = format!("{}_", stem);
+
+    for texture in textures {
+        let Some(image_name) = texture["image"].as_str() else {
+            continue;
+        };
+
+        // only page files written by the export are touched
+        let is_page = image_name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(".png"))
+            .map_or(false, |page| page.parse::<usize>().is_ok());
+
+        if !is_page {
+            continue;
+        }
+
+        let page_path = path.with_file_name(image_name);
+
+        if let Err(err) = fs::remove_file(&page_path) {
+            log::warn!("Removing atlas page {:?} failed: {}", page_path, err);
+        }
+    }
+}
+
+fn to_rgba_image(image: &ImageData, name: &str) -> Result<Option<RgbaImage>> {
+    if image.is_empty() {
+        return Ok(None);
+    }
+
+    RgbaImage::from_raw(image.width, image.height, image.pixels.clone())
+        .map(Some)
+        .ok_or_else(|| anyhow!("Image of {} is not an RGBA image.", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::RgbaImage;
+    use paperdoll_tar::paperdoll::Point;
+
+    use super::{pack_sprites, AtlasSprite, ATLAS_MAX_SIZE, ATLAS_PADDING};
+
+    fn sprite(width: u32, height: u32) -> AtlasSprite {
+        AtlasSprite {
+            name: String::new(),
+            image: RgbaImage::new(width, height),
+            pivot: Point::default(),
+        }
+    }
+
+    #[test]
+    fn places_tallest_first_on_shelves() {
+        let sprites = vec![sprite(10, 10), sprite(20, 30), sprite(5, 10)];
+
+        let pages = pack_sprites(&sprites);
+
+        assert_eq!(pages.len(), 1);
+        assert_eq!(
+            pages[0].placements,
+            vec![
+                (1, 0, 0),
+                (0, 20 + ATLAS_PADDING, 0),
+                (2, 30 + ATLAS_PADDING * 2, 0)
+            ]
+        );
+        assert_eq!(pages[0].width, 35 + ATLAS_PADDING * 2);
+        assert_eq!(pages[0].height, 30);
+    }
+
+    #[test]
+    fn wraps_to_next_shelf_and_page() {
+        // two sprites and their padding fill a shelf, two shelves fill a page
+        let size = ATLAS_MAX_SIZE / 2 - ATLAS_PADDING;
+        let step = size + ATLAS_PADDING;
+
+        let sprites: Vec<AtlasSprite> = (0..5).map(|_| sprite(size, size)).collect();
+
+        let pages = pack_sprites(&sprites);
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(
+            pages[0].placements,
+            vec![(0, 0, 0), (1, step, 0), (2, 0, step), (3, step, step)]
+        );
+        assert_eq!(pages[1].placements, vec![(4, 0, 0)]);
+    }
+
+    #[test]
+    fn oversized_sprites_get_own_page() {
+        let sprites = vec![sprite(ATLAS_MAX_SIZE + 1, 4), sprite(4, 4)];
+
+        let pages = pack_sprites(&sprites);
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].placements, vec![(0, 0, 0)]);
+        assert_eq!(pages[0].width, ATLAS_MAX_SIZE + 1);
+        assert_eq!(pages[1].placements, vec![(1, 0, 0)]);
+    }
+
+    #[test]
+    fn no_sprites_no_pages() {
+        assert!(pack_sprites(&[]).is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+use std::{path::PathBuf, process::exit};
+
+use clap::{Parser, Subcommand};
+use ppd_editor::cli::{self, MAX_COMBINATIONS};
+
+#[derive(Parser)]
+#[command(name = "ppd-cli")]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print dolls, slots and fragments of a paperdoll file
+    Info { file: PathBuf },
+    /// Check a paperdoll file for problems, fails only on errors and not on warnings
+    Validate { file: PathBuf },
+    /// Pack a folder created by `unpack` into a paperdoll file
+    Pack {
+        dir: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Render a doll to an image
+    Render {
+        file: PathBuf,
+        #[arg(long)]
+        doll: Option<u32>,
+        /// Fragment placed into a slot, in the form of <slot>=<fragment>
+        #[arg(long = "slot", value_parser = parse_slot)]
+        slots: Vec<(u32, u32)>,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Render combinations of slot candidates of a doll to a sprite sheet with a JSON index
+    Sheet {
+        file: PathBuf,
+        #[arg(long)]
+        doll: Option<u32>,
+        /// Slot to combine, optionally limited to some candidates, in the form of <slot>[=<fragment>,...]
+        #[arg(long = "slot", value_parser = parse_sheet_slot)]
+        slots: Vec<(u32, Option<Vec<u32>>)>,
+        /// Number of columns of the sheet
+        #[arg(long)]
+        columns: Option<u32>,
+        /// Maximum number of combinations
+        #[arg(long, default_value_t = MAX_COMBINATIONS)]
+        limit: usize,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Unpack a paperdoll file into a folder with a JSON manifest and png images
+    Unpack {
+        file: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
+fn main() {
+    env_logger::init();
+
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Info { file } => cli::info(file).map(|_| true),
+        Command::Validate { file } => cli::validate(file),
+        Command::Pack { dir, output } => cli::pack(dir, output).map(|_| true),
+        Command::Render {
+            file,
+            doll,
+            slots,
+            output,
+        } => cli::render(file, doll, &slots, output).map(|_| true),
+        Command::Sheet {
+            file,
+            doll,
+            slots,
+            columns,
+            limit,
+            output,
+        } => cli::sheet(file, doll, slots, columns, limit, output).map(|_| true),
+        Command::Unpack { file, output } => cli::unpack(file, output).map(|_| true),
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => exit(1),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+
+            exit(1);
+        }
+    }
+}
+
+fn parse_slot(value: &str) -> Result<(u32, u32), String> {
+    let (slot, fragment) = value
+        .split_once('=')
+        .ok_or_else(|| format!("'{}' is not in the form of <slot>=<fragment>", value))?;
+
+    let slot = slot
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid slot id", slot))?;
+    let fragment = fragment
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid fragment id", fragment))?;
+
+    Ok((slot, fragment))
+}
+
+fn parse_sheet_slot(value: &str) -> Result<(u32, Option<Vec<u32>>), String> {
+    let (slot, fragments) = match value.split_once('=') {
+        Some((slot, fragments)) => (slot, Some(fragments)),
+        None => (value, None),
+    };
+
+    let slot = slot
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid slot id", slot))?;
+
+    let fragments = fragments
+        .map(|fragments| {
+            fragments
+                .split(',')
+                .map(|fragment| {
+                    fragment
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("'{}' is not a valid fragment id", fragment))
+                })
+                .collect::<Result<Vec<u32>, String>>()
+        })
+        .transpose()?;
+
+    Ok((slot, fragments))
+}
@@ -10,6 +10,12 @@ use ppd_editor::viewer::{self, APP_ID};
 struct Cli {
     #[arg(short, long)]
     open: Option<String>,
+    /// Id of the doll to show
+    #[arg(short, long, requires = "open")]
+    doll: Option<u32>,
+    /// Outfit code copied from the viewer, it already names the doll to show
+    #[arg(long, requires = "open", conflicts_with = "doll")]
+    outfit: Option<String>,
 }
 
 fn main() {
@@ -48,7 +54,7 @@ fn main() {
     if let Err(err) = eframe::run_native(
         viewer::APP_TITLE,
         native_options,
-        Box::new(|cc| viewer::setup_eframe(cc, ppd, path)),
+        Box::new(move |cc| viewer::setup_eframe(cc, ppd, path, cli.doll, cli.outfit)),
     ) {
         log::error!("Failed to run ppd-viewer: {}", err);
     }
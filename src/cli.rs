@@ -0,0 +1,275 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{anyhow, bail, Result};
+use image::RgbaImage;
+use paperdoll_tar::paperdoll::{Paperdoll, PaperdollFactory};
+
+use crate::{
+    common::{save_image, sorted_ids},
+    folder,
+    sheet::{self, SheetSlot},
+    validation,
+};
+
+pub use crate::sheet::MAX_COMBINATIONS;
+
+pub fn info<P>(path: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let ppd = paperdoll_tar::load(&path)?;
+
+    println!("Name: {}", ppd.meta.name);
+
+    println!();
+    println!("Dolls:");
+
+    for id in sorted_ids(ppd.dolls().map(|(id, _)| *id)) {
+        if let Some(doll) = ppd.get_doll(id) {
+            println!(
+                "  [{}] {} ({} x {}) slots: {:?}",
+                id,
+                display_desc(&doll.desc),
+                doll.width,
+                doll.height,
+                doll.slots
+            );
+        }
+    }
+
+    println!();
+    println!("Slots:");
+
+    for id in sorted_ids(ppd.slots().map(|(id, _)| *id)) {
+        if let Some(slot) = ppd.get_slot(id) {
+            println!(
+                "  [{}] {}{} ({} x {}) candidates: {:?}",
+                id,
+                display_desc(&slot.desc),
+                if slot.required { " (required)" } else { "" },
+                slot.width,
+                slot.height,
+                slot.candidates
+            );
+        }
+    }
+
+    println!();
+    println!("Fragments:");
+
+    for id in sorted_ids(ppd.fragments().map(|(id, _)| *id)) {
+        if let Some(fragment) = ppd.get_fragment(id) {
+            println!(
+                "  [{}] {} ({} x {})",
+                id,
+                display_desc(&fragment.desc),
+                fragment.image.width,
+                fragment.image.height
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn pack<P, Q>(dir: P, output: Q) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let mut manifest = folder::pack_folder(dir)?;
+
+    paperdoll_tar::save(&mut manifest, output)?;
+
+    Ok(())
+}
+
+pub fn render<P, Q>(path: P, doll: Option<u32>, slots: &[(u32, u32)], output: Q) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let ppd = paperdoll_tar::load(&path)?;
+
+    let doll_id = resolve_doll(&ppd, doll)?;
+
+    let doll = ppd
+        .get_doll(doll_id)
+        .ok_or_else(|| anyhow!("Doll {} not found.", doll_id))?;
+
+    let mut paperdoll = default_paperdoll(&ppd, doll_id);
+
+    for (slot_id, fragment_id) in slots {
+        if !doll.slots.contains(slot_id) {
+            bail!("Slot {} does not belong to doll {}.", slot_id, doll_id);
+        }
+
+        let slot = ppd
+            .get_slot(*slot_id)
+            .ok_or_else(|| anyhow!("Slot {} not found.", slot_id))?;
+
+        if !slot.candidates.contains(fragment_id) {
+            bail!(
+                "Fragment {} is not a candidate of slot {}.",
+                fragment_id,
+                slot_id
+            );
+        }
+
+        paperdoll.slot_map.insert(*slot_id, *fragment_id);
+    }
+
+    let image = ppd.render_paperdoll(&paperdoll)?;
+
+    let image = RgbaImage::from_raw(image.width, image.height, image.pixels)
+        .ok_or_else(|| anyhow!("Invalid image rendered for doll {}.", doll_id))?;
+
+    save_image(&image, output.as_ref())?;
+
+    Ok(())
+}
+
+pub fn sheet<P, Q>(
+    path: P,
+    doll: Option<u32>,
+    slots: Vec<(u32, Option<Vec<u32>>)>,
+    columns: Option<u32>,
+    limit: usize,
+    output: Q,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let ppd = paperdoll_tar::load(&path)?;
+
+    let doll_id = resolve_doll(&ppd, doll)?;
+
+    let doll = ppd
+        .get_doll(doll_id)
+        .ok_or_else(|| anyhow!("Doll {} not found.", doll_id))?;
+
+    // combine every slot of the doll when none is given
+    let slots: Vec<SheetSlot> = if slots.is_empty() {
+        doll.slots
+            .iter()
+            .map(|slot_id| SheetSlot {
+                slot: *slot_id,
+                candidates: None,
+            })
+            .collect()
+    } else {
+        slots
+            .into_iter()
+            .map(|(slot, candidates)| SheetSlot { slot, candidates })
+            .collect()
+    };
+
+    let combinations = sheet::combinations(&ppd, doll_id, &slots, &HashMap::new(), limit)?;
+
+    let count = combinations.len();
+
+    let (image, index) = sheet::render_sprite_sheet(&ppd, doll_id, combinations, columns)?;
+
+    sheet::save_sprite_sheet(&image, &index, &output)?;
+
+    println!(
+        "Rendered {} combinations to {}.",
+        count,
+        output.as_ref().display()
+    );
+
+    Ok(())
+}
+
+pub fn unpack<P, Q>(path: P, dir: Q) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let ppd = paperdoll_tar::load(&path)?;
+
+    folder::unpack_to_folder(&ppd, dir)
+}
+
+pub fn validate<P>(path: P) -> Result<bool>
+where
+    P: AsRef<Path>,
+{
+    let ppd = paperdoll_tar::load(&path)?;
+
+    let mut errors = vec![];
+    let mut warnings = vec![];
+
+    for problem in validation::validate(&ppd) {
+        if problem.is_error() {
+            errors.push(problem.message);
+        } else {
+            warnings.push(problem.message);
+        }
+    }
+
+    for doll_id in sorted_ids(ppd.dolls().map(|(id, _)| *id)) {
+        if let Err(err) = ppd.render_paperdoll(&default_paperdoll(&ppd, doll_id)) {
+            errors.push(format!("Doll {} can not be rendered: {}", doll_id, err));
+        }
+    }
+
+    for error in &errors {
+        println!("error: {}", error);
+    }
+
+    for warning in &warnings {
+        println!("warning: {}", warning);
+    }
+
+    if errors.is_empty() && warnings.is_empty() {
+        println!("No problems found.");
+    }
+
+    // warnings alone do not fail the validation
+    Ok(errors.is_empty())
+}
+
+fn default_paperdoll(ppd: &PaperdollFactory, doll_id: u32) -> Paperdoll {
+    let mut slot_map = HashMap::new();
+
+    if let Some(doll) = ppd.get_doll(doll_id) {
+        for slot_id in &doll.slots {
+            let Some(slot) = ppd.get_slot(*slot_id) else {
+                continue;
+            };
+
+            if !slot.required {
+                continue;
+            }
+
+            if let Some(fragment_id) = slot.candidates.first() {
+                slot_map.insert(*slot_id, *fragment_id);
+            }
+        }
+    }
+
+    Paperdoll {
+        doll: doll_id,
+        slot_map,
+    }
+}
+
+fn resolve_doll(ppd: &PaperdollFactory, doll: Option<u32>) -> Result<u32> {
+    match doll {
+        Some(id) => Ok(id),
+        None => sorted_ids(ppd.dolls().map(|(id, _)| *id))
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("No dolls found in the file.")),
+    }
+}
+
+fn display_desc(desc: &str) -> &str {
+    if desc.is_empty() {
+        "-"
+    } else {
+        desc
+    }
+}
@@ -1,6 +1,11 @@
-use std::{collections::HashMap, fs::read};
+use std::{
+    collections::HashMap,
+    fs::{read, File},
+    io::BufWriter,
+    path::Path,
+};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Result};
 use eframe::{
     egui::{
         self, widget_text::WidgetTextGalley, Context, CursorIcon, FontData, FontDefinitions,
@@ -11,7 +16,12 @@ use eframe::{
 use font_kit::{
     family_name::FamilyName, handle::Handle, properties::Properties, source::SystemSource,
 };
+use image::{
+    codecs::webp::{WebPEncoder, WebPQuality},
+    ColorType, RgbaImage,
+};
 use paperdoll_tar::paperdoll::{Doll, ImageData, PaperdollFactory};
+use serde::de::DeserializeOwned;
 
 pub struct TextureData {
     pub width: u32,
@@ -46,6 +56,11 @@ pub(crate) fn drag_move(response: &Response, scale: f32, ctx: &Context) -> Vec2
     response.drag_delta() / scale
 }
 
+// turns a name into a file name, using the default when the name is empty
+pub(crate) fn file_name_or(name: &str, default: &str) -> String {
+    if name.is_empty() { default } else { name }.replace(" ", "_")
+}
+
 pub(crate) fn layout_text_widget(
     ui: &mut Ui,
     text: impl Into<WidgetText>,
@@ -105,6 +120,52 @@ pub(crate) fn load_fonts(ctx: &Context) {
     ctx.set_fonts(fonts);
 }
 
+// a value that fails to parse is skipped so that the other keys are still restored
+pub(crate) fn restore_storage_value<T>(storage: &dyn eframe::Storage, key: &str) -> Option<T>
+where
+    T: DeserializeOwned,
+{
+    let value = storage.get_string(key)?;
+
+    match serde_json::from_str(&value) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            log::error!("Restoring app data {} failed: {}", key, err);
+
+            None
+        }
+    }
+}
+
+// saves by the extension of the path, with webp always encoded losslessly
+pub(crate) fn save_image(image: &RgbaImage, path: &Path) -> Result<()> {
+    let is_webp = path
+        .extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("webp"));
+
+    if is_webp {
+        save_webp(image, path)
+    } else {
+        image.save(path)?;
+
+        Ok(())
+    }
+}
+
+// image only encodes lossy webp by default
+pub(crate) fn save_webp(image: &RgbaImage, path: &Path) -> Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+
+    WebPEncoder::new_with_quality(file, WebPQuality::lossless()).encode(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        ColorType::Rgba8,
+    )?;
+
+    Ok(())
+}
+
 pub(crate) fn setup_style(ctx: &Context) {
     let mut style = (*ctx.style()).clone();
 
@@ -113,6 +174,33 @@ pub(crate) fn setup_style(ctx: &Context) {
     ctx.set_style(style);
 }
 
+pub(crate) fn sorted_ids(ids: impl Iterator<Item = u32>) -> Vec<u32> {
+    let mut ids: Vec<u32> = ids.collect();
+
+    ids.sort();
+
+    ids
+}
+
+pub(crate) fn upload_fragment_textures(
+    ppd: &PaperdollFactory,
+    ctx: &Context,
+) -> HashMap<u32, TextureData> {
+    let mut textures_fragment = HashMap::new();
+
+    for (id, fragment) in ppd.fragments() {
+        if fragment.image.is_empty() {
+            continue;
+        }
+
+        let texture = upload_image_to_texture(&fragment.image, "fragment", ctx);
+
+        textures_fragment.insert(*id, texture);
+    }
+
+    textures_fragment
+}
+
 pub(crate) fn upload_image_to_texture(
     image: &ImageData,
     name: impl Into<String>,
@@ -150,19 +238,7 @@ pub(crate) fn upload_ppd_textures(
         textures_doll.insert(*id, texture);
     }
 
-    let mut textures_fragment = HashMap::new();
-
-    for (id, fragment) in ppd.fragments() {
-        if fragment.image.is_empty() {
-            continue;
-        }
-
-        let texture = upload_image_to_texture(&fragment.image, "fragment", ctx);
-
-        textures_fragment.insert(*id, texture);
-    }
-
-    (textures_doll, textures_fragment)
+    (textures_doll, upload_fragment_textures(ppd, ctx))
 }
 
 fn add_font(fonts: &mut FontDefinitions, font: FontData, font_name: &str) {
@@ -0,0 +1,212 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use paperdoll_tar::paperdoll::{Fragment, ImageData, PaperdollFactory};
+
+use crate::common::sorted_ids;
+
+// groups of fragments with identical images and pivots, ids in each group are sorted,
+// fragments sharing an image but not the pivot are kept apart as merging would move them
+pub fn find_duplicate_fragments(ppd: &PaperdollFactory) -> Vec<Vec<u32>> {
+    let mut buckets: HashMap<u64, Vec<u32>> = HashMap::new();
+
+    for id in sorted_ids(ppd.fragments().map(|(id, _)| *id)) {
+        if let Some(key) = ppd.get_fragment(id).and_then(fragment_key) {
+            buckets.entry(key).or_default().push(id);
+        }
+    }
+
+    let mut groups = vec![];
+
+    for ids in buckets.into_values() {
+        // fragments sharing a hash are compared once more to rule out collisions
+        let mut pending = ids;
+
+        while !pending.is_empty() {
+            let first = pending.remove(0);
+
+            let (same, rest): (Vec<u32>, Vec<u32>) = pending
+                .into_iter()
+                .partition(|id| is_same_fragment(ppd, first, *id));
+
+            if !same.is_empty() {
+                let mut group = vec![first];
+
+                group.extend(same);
+
+                groups.push(group);
+            }
+
+            pending = rest;
+        }
+    }
+
+    groups.sort();
+
+    groups
+}
+
+// finds an existing fragment with the same image
+pub fn find_fragment_with_image(ppd: &PaperdollFactory, image: &ImageData) -> Option<u32> {
+    if image.is_empty() {
+        return None;
+    }
+
+    sorted_ids(ppd.fragments().map(|(id, _)| *id))
+        .into_iter()
+        .find(|id| {
+            ppd.get_fragment(*id)
+                .map_or(false, |fragment| is_same_image(&fragment.image, image))
+        })
+}
+
+// keeps the first fragment of the group and points every candidate list to it,
+// returns the number of removed fragments
+pub fn merge_duplicate_fragments(ppd: &mut PaperdollFactory, group: &[u32]) -> usize {
+    let Some((kept, removed)) = group.split_first() else {
+        return 0;
+    };
+
+    if ppd.get_fragment(*kept).is_none() {
+        return 0;
+    }
+
+    let removed: Vec<u32> = removed
+        .iter()
+        .copied()
+        .filter(|id| ppd.get_fragment(*id).is_some())
+        .collect();
+
+    let slot_ids: Vec<u32> = ppd.slots().map(|(id, _)| *id).collect();
+
+    for slot_id in slot_ids {
+        let Some(slot) = ppd.get_slot_mut(slot_id) else {
+            continue;
+        };
+
+        if !slot.candidates.iter().any(|id| removed.contains(id)) {
+            continue;
+        }
+
+        let mut candidates = vec![];
+
+        for id in &slot.candidates {
+            let id = if removed.contains(id) { *kept } else { *id };
+
+            if !candidates.contains(&id) {
+                candidates.push(id);
+            }
+        }
+
+        slot.candidates = candidates;
+    }
+
+    for id in &removed {
+        ppd.remove_fragment(*id);
+    }
+
+    removed.len()
+}
+
+fn fragment_key(fragment: &Fragment) -> Option<u64> {
+    if fragment.image.is_empty() {
+        return None;
+    }
+
+    let mut hasher = DefaultHasher::new();
+
+    fragment.image.width.hash(&mut hasher);
+    fragment.image.height.hash(&mut hasher);
+    fragment.image.pixels.hash(&mut hasher);
+    fragment.pivot.x.to_bits().hash(&mut hasher);
+    fragment.pivot.y.to_bits().hash(&mut hasher);
+
+    Some(hasher.finish())
+}
+
+pub fn is_same_image(a: &ImageData, b: &ImageData) -> bool {
+    !a.is_empty() && a.width == b.width && a.height == b.height && a.pixels == b.pixels
+}
+
+fn is_same_fragment(ppd: &PaperdollFactory, a: u32, b: u32) -> bool {
+    match (ppd.get_fragment(a), ppd.get_fragment(b)) {
+        (Some(a), Some(b)) => {
+            is_same_image(&a.image, &b.image) && a.pivot.x == b.pivot.x && a.pivot.y == b.pivot.y
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use paperdoll_tar::paperdoll::{ColorType, ImageData, PaperdollFactory};
+
+    use super::{find_duplicate_fragments, merge_duplicate_fragments};
+
+    fn image(value: u8) -> ImageData {
+        ImageData {
+            width: 1,
+            height: 1,
+            color_type: ColorType::Rgba,
+            pixels: vec![value; 4],
+        }
+    }
+
+    fn add_fragment(ppd: &mut PaperdollFactory, value: u8, pivot_x: f32) -> u32 {
+        let id = ppd.add_fragment().unwrap();
+
+        let fragment = ppd.get_fragment_mut(id).unwrap();
+        fragment.image = image(value);
+        fragment.pivot.x = pivot_x;
+
+        id
+    }
+
+    #[test]
+    fn groups_same_images_and_pivots() {
+        let mut ppd = PaperdollFactory::default();
+
+        let a = add_fragment(&mut ppd, 1, 0.0);
+        let b = add_fragment(&mut ppd, 2, 0.0);
+        let c = add_fragment(&mut ppd, 1, 0.0);
+        let d = add_fragment(&mut ppd, 1, 5.0);
+
+        assert_eq!(find_duplicate_fragments(&ppd), vec![vec![a, c]]);
+
+        assert!(ppd.get_fragment(b).is_some());
+        assert!(ppd.get_fragment(d).is_some());
+    }
+
+    #[test]
+    fn merge_points_candidates_to_kept_fragment() {
+        let mut ppd = PaperdollFactory::default();
+
+        let a = add_fragment(&mut ppd, 1, 0.0);
+        let b = add_fragment(&mut ppd, 2, 0.0);
+        let c = add_fragment(&mut ppd, 1, 0.0);
+
+        let slot = ppd.add_slot().unwrap();
+        ppd.get_slot_mut(slot).unwrap().candidates = vec![c, b, a];
+
+        assert_eq!(merge_duplicate_fragments(&mut ppd, &[a, c]), 1);
+
+        assert!(ppd.get_fragment(c).is_none());
+        assert_eq!(ppd.get_slot(slot).unwrap().candidates, vec![a, b]);
+        assert!(find_duplicate_fragments(&ppd).is_empty());
+    }
+
+    #[test]
+    fn merge_ignores_missing_fragments() {
+        let mut ppd = PaperdollFactory::default();
+
+        let a = add_fragment(&mut ppd, 1, 0.0);
+
+        assert_eq!(merge_duplicate_fragments(&mut ppd, &[a, a + 100]), 0);
+        assert_eq!(merge_duplicate_fragments(&mut ppd, &[a + 100, a]), 0);
+        assert_eq!(merge_duplicate_fragments(&mut ppd, &[]), 0);
+
+        assert!(ppd.get_fragment(a).is_some());
+    }
+}
@@ -1,14 +1,24 @@
 mod actions;
+mod align;
 mod canvas;
 mod config;
 mod example;
+mod guides;
+mod history;
 mod menu;
+mod recovery;
+mod relink;
 mod shortcut;
 mod storage;
 mod ui;
+mod watcher;
 mod widgets;
 
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+    time::Duration,
+};
 
 use eframe::{
     egui::Context,
@@ -24,13 +34,24 @@ use which::which;
 use crate::{
     adapter::{DollAdapter, FragmentAdapter, SlotAdapter, DOLL_DEFAULT_SIZE},
     common::{load_fonts, setup_style, TextureData},
+    validation::Problem,
     viewport::Viewport,
 };
 
 use self::{
-    actions::Action, canvas::CanvasState, config::Config, shortcut::Shortcut, storage::Storage,
+    actions::Action,
+    canvas::CanvasState,
+    config::Config,
+    guides::{GuideOrientation, Guides},
+    history::History,
+    relink::RelinkEntry,
+    shortcut::Shortcut,
+    storage::Storage,
+    watcher::ImageWatcher,
 };
 
+const NOTIFICATION_DURATION: f64 = 3.0;
+
 pub const APP_CMD: &'static str = "ppd-editor";
 pub const APP_ID: &'static str = "io.github.fralonra.PpdEditor";
 pub const APP_TITLE: &'static str = "Paperdoll Editor";
@@ -115,6 +136,32 @@ struct EditorApp {
 
     // project core
     ppd: PaperdollFactory,
+    is_dirty: bool,
+    autosave_time: f64,
+    is_recovery_pending: bool,
+
+    // guides
+    guides: Guides,
+
+    // watching source images
+    image_watcher: ImageWatcher,
+
+    // notification and the time it expires
+    notification: Option<(String, f64)>,
+
+    // relinking missing images
+    relink_root: Option<PathBuf>,
+    relink_entries: Vec<RelinkEntry>,
+
+    // history
+    history: History,
+    history_pending: Option<String>,
+
+    // problems
+    problems: Vec<Problem>,
+
+    // groups of fragments sharing the same image
+    duplicate_fragments: Vec<Vec<u32>>,
 
     // textures
     textures_doll: HashMap<u32, TextureData>,
@@ -124,14 +171,17 @@ struct EditorApp {
     canvas_state: CanvasState,
     canvas_original_pos_anchor: Option<Point>,
     canvas_original_pos_slot_and_drag_offset: Option<(Vec<Point>, Vec2)>,
+    canvas_dragging_guide: Option<(GuideOrientation, usize)>,
+    canvas_selection_origin: Option<Pos2>,
     fragments_filter_keyword: String,
     has_drag_value_focused: bool,
     cursor_position: Option<Pos2>,
     align_basis_slots: HashSet<u32>,
     associated_slots: HashSet<u32>,
     locked_slots: HashSet<u32>,
+    selected_slots: HashSet<u32>,
     visible_slots: HashSet<u32>,
-    slot_copy: Option<u32>,
+    slot_copy: Vec<u32>,
 
     // adapters
     adapter_doll: Option<DollAdapter>,
@@ -147,11 +197,21 @@ struct EditorApp {
     dialog_visible: bool,
     dialog_option: DialogOption,
 
+    // unsaved changes
+    is_changes_discarded: bool,
+    is_close_confirmed: bool,
+    unsaved_changes_next_action: Option<Action>,
+
     // window visible
     window_about_visible: bool,
     window_associated_slots_visible: bool,
     window_doll_visible: bool,
+    window_duplicates_visible: bool,
     window_fragment_visible: bool,
+    window_guides_visible: bool,
+    window_history_visible: bool,
+    window_problems_visible: bool,
+    window_relink_visible: bool,
     window_slot_visible: bool,
 
     // window status
@@ -174,6 +234,22 @@ impl App for EditorApp {
         if let Err(err) = self.handle_actions(ctx, frame) {
             log::error!("{}", err);
         }
+
+        self.commit_pending_history(ctx);
+
+        self.autosave(ctx);
+
+        self.watch_images(ctx);
+    }
+
+    fn on_close_event(&mut self) -> bool {
+        if self.is_dirty && !self.is_close_confirmed {
+            self.actions.push_back(Action::AppQuit);
+
+            return false;
+        }
+
+        true
     }
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
@@ -208,10 +284,7 @@ impl EditorApp {
         let mut storage = Storage::default();
 
         if let Some(s) = cc.storage {
-            match storage.restore(s) {
-                Ok(()) => log::info!("Restoring app data successfully."),
-                Err(err) => log::error!("Restoring app data failed: {}", err),
-            }
+            storage.restore(s);
         }
 
         let mut config = Config::default();
@@ -225,13 +298,36 @@ impl EditorApp {
         let has_viewer_installed = true;
 
         Self {
-            actions: VecDeque::from([Action::PpdChanged, Action::AppTitleChanged(path)]),
+            actions: VecDeque::from([
+                Action::PpdChanged,
+                Action::AppTitleChanged(path),
+                Action::RecoveryCheck,
+            ]),
             config,
             shortcut: Shortcut::default(),
             storage,
             viewport: Viewport::default(),
 
             ppd,
+            is_dirty: false,
+            autosave_time: 0.0,
+            is_recovery_pending: false,
+
+            guides: Guides::default(),
+
+            image_watcher: ImageWatcher::default(),
+
+            notification: None,
+
+            relink_root: None,
+            relink_entries: vec![],
+
+            history: History::default(),
+            history_pending: None,
+
+            problems: vec![],
+
+            duplicate_fragments: vec![],
 
             textures_doll: HashMap::new(),
             textures_fragment: HashMap::new(),
@@ -239,14 +335,17 @@ impl EditorApp {
             canvas_state: CanvasState::default(),
             canvas_original_pos_anchor: None,
             canvas_original_pos_slot_and_drag_offset: None,
+            canvas_dragging_guide: None,
+            canvas_selection_origin: None,
             fragments_filter_keyword: String::default(),
             has_drag_value_focused: false,
             cursor_position: None,
             align_basis_slots: HashSet::new(),
             associated_slots: HashSet::new(),
             locked_slots: HashSet::new(),
+            selected_slots: HashSet::new(),
             visible_slots: HashSet::new(),
-            slot_copy: None,
+            slot_copy: vec![],
 
             adapter_doll: None,
             adapter_fragment: None,
@@ -259,10 +358,19 @@ impl EditorApp {
             dialog_visible: false,
             dialog_option: DialogOption::default(),
 
+            is_changes_discarded: false,
+            is_close_confirmed: false,
+            unsaved_changes_next_action: None,
+
             window_about_visible: false,
             window_associated_slots_visible: false,
             window_doll_visible: false,
+            window_duplicates_visible: false,
             window_fragment_visible: false,
+            window_guides_visible: false,
+            window_history_visible: false,
+            window_problems_visible: false,
+            window_relink_visible: false,
             window_slot_visible: false,
 
             window_doll_error: None,
@@ -279,9 +387,18 @@ impl EditorApp {
             || self.window_associated_slots_visible
             || self.window_doll_visible
             || self.window_fragment_visible
+            || self.window_relink_visible
             || self.window_slot_visible
     }
 
+    fn notify(&mut self, text: impl Into<String>, ctx: &Context) {
+        let now = ctx.input(|i| i.time);
+
+        self.notification = Some((text.into(), now + NOTIFICATION_DURATION));
+
+        ctx.request_repaint_after(Duration::from_secs_f64(NOTIFICATION_DURATION));
+    }
+
     fn prepare(&mut self) {
         self.has_drag_value_focused = false;
     }
@@ -1,4 +1,5 @@
 use std::{
+    fs, mem,
     path::{Path, PathBuf},
     process::Command,
 };
@@ -6,7 +7,7 @@ use std::{
 use anyhow::{anyhow, bail, Result};
 use eframe::{
     egui::Context,
-    epaint::{Pos2, Vec2},
+    epaint::{ahash::HashSet, Pos2, Vec2},
     Frame,
 };
 use paperdoll_tar::{
@@ -16,14 +17,37 @@ use paperdoll_tar::{
 
 use crate::{
     adapter::{DollAdapter, FragmentAdapter, ImageAdapter, SlotAdapter, DOLL_DEFAULT_SIZE},
+    atlas::export_atlas,
     common::{
-        allocate_size_fit_in_rect, upload_image_to_texture, upload_ppd_textures, TextureData,
+        allocate_size_fit_in_rect, file_name_or, upload_image_to_texture, upload_ppd_textures,
+        TextureData,
     },
-    fs::{create_file, open_image_rgba, select_file, select_texture, select_textures},
+    duplicates::{
+        find_duplicate_fragments, find_fragment_with_image, is_same_image,
+        merge_duplicate_fragments,
+    },
+    folder::{pack_folder, unpack_to_folder},
+    fs::{
+        create_file, export_json, export_rpy, export_tscn, open_image_rgba, select_file,
+        select_folder, select_ora, select_texture, select_textures,
+    },
+    godot::export_godot,
+    ora::{OraFile, OraGroup, OraLayer},
+    renpy::export_renpy,
+    validation::{validate, ProblemTarget},
     viewport::Viewport,
 };
 
-use super::{canvas::CanvasState, example::Example, DialogOption, EditorApp, APP_TITLE};
+use super::{
+    align::{SlotAlign, SlotDistribute},
+    canvas::CanvasState,
+    example::Example,
+    guides::{GuideOrientation, Guides},
+    history::ChangedImages,
+    recovery::{find_recovery, remove_recovery},
+    relink::{find_relinks, RelinkTarget},
+    DialogOption, EditorApp, APP_TITLE,
+};
 
 pub enum Action {
     AppQuit,
@@ -33,16 +57,25 @@ pub enum Action {
     AssociatedSlotsEdit(u32),
     AssociatedSlotsSelectAll,
     AssociatedSlotsUnselectAll,
+    AutosaveIntervalChanged(u64),
     CandidateLower(Option<u32>, u32),
     CandidateLowerBottom(Option<u32>, u32),
     CandidateRaise(Option<u32>, u32),
     CandidateRaiseTop(Option<u32>, u32),
+    CanvasLayoutGridSize(u32),
+    CanvasShowLayoutGrid(bool),
+    CanvasShowPixelGrid(bool),
+    CanvasShowRulers(bool),
     CanvasShowSlotBoundaries(bool),
+    CanvasSnapToGrid(bool),
     CanvasStateChanged(CanvasState),
+    ChangesDiscard,
+    ChangesSave,
     CursorMoved(Option<Pos2>),
     DollCreate,
     DollAdapterBackgroundRemove,
     DollAdapterBackgroundUpload,
+    DollBackgroundReload(u32),
     DollBackgroundRemove(u32),
     DollBackgroundUpload(u32),
     DollEdit(u32),
@@ -50,32 +83,56 @@ pub enum Action {
     DollRemoveConfirm(u32),
     DollRemoveRequest(u32),
     DollResizeToBackground(u32),
+    DuplicatesMerge(Vec<u32>),
+    DuplicatesMergeAll,
+    ExportAtlas(bool),
+    ExportGodot(u32),
+    ExportRenpy,
     FileNew,
     FileOpen,
     FileOpenPath(PathBuf),
+    FilePack,
     FileSave,
     FileSaveAs,
+    FileUnpack,
     FragmentAdapterBackgroundUpload,
     FragmentBackgroundUpload(u32),
     FragmentCreate,
     FragmentCreateFromBatchImages,
+    FragmentCreateFromImages(Vec<(PathBuf, ImageData)>),
+    FragmentCreateFromOra(bool),
     FragmentEdit(u32),
     FragmentEditCancel(Option<u32>),
     FragmentEditConfirm(Option<u32>),
+    FragmentReload(u32),
     FragmentRemoveConfirm(u32),
     FragmentRemoveRequest(u32),
     FragmentUpdateTexture(u32, PathBuf, TextureData, Vec<u8>),
+    GuideAdd(u32, GuideOrientation),
+    GuideRemove(u32, GuideOrientation, usize),
+    GuidesChanged,
+    HistoryRedo,
+    HistoryUndo,
+    ImagesReload,
     OpenViewer,
     PpdLoad(PaperdollFactory),
     PpdLoadExample(Example),
     PpdChanged,
+    ProblemSelect(ProblemTarget),
     RecentFilesClean,
+    RecoveryCheck,
+    RecoveryDiscard(PathBuf),
+    RecoveryRestore(PathBuf),
+    RelinkConfirm,
+    RelinkSelectRoot,
     SlotAdapterFragmentFilter,
     SlotAddCandidate(Option<u32>, u32),
     SlotAddCandidates(Option<u32>, Vec<u32>),
     SlotAddPosition(Option<u32>),
+    SlotAlign(SlotAlign),
     SlotCopy(u32),
     SlotCreate,
+    SlotDistribute(SlotDistribute),
     SlotDuplicate(u32, u32),
     SlotEdit(u32),
     SlotEditCancel(Option<u32>),
@@ -90,27 +147,69 @@ pub enum Action {
     SlotRemoveConfirm(u32),
     SlotRemovePosition(Option<u32>, usize),
     SlotRemoveRequest(u32),
+    SlotRemoveSelectedConfirm(Vec<u32>),
     ViewportCenter,
     ViewportFit,
     ViewportMove(Vec2),
     ViewportZoomReset,
     ViewportZoomTo(f32),
+    WatchImages(bool),
+    WatchedImageChanged(PathBuf),
     WindowAboutVisible(bool),
     WindowAssociatedSlotsVisible(bool),
     WindowDollVisible(bool),
+    WindowDuplicatesVisible(bool),
     WindowFragmentVisible(bool),
+    WindowGuidesVisible(bool),
+    WindowHistoryVisible(bool),
+    WindowProblemsVisible(bool),
+    WindowRelinkVisible(bool),
     WindowSlotVisible(bool),
 }
 
 impl EditorApp {
     pub(super) fn handle_actions(&mut self, ctx: &Context, frame: &mut Frame) -> Result<()> {
+        let mut history_desc: Option<String> = None;
+
+        // actions handled before a failing one still get their history entry
+        let result = self.run_actions(ctx, frame, &mut history_desc);
+
+        if let Some(desc) = history_desc {
+            self.history_pending = None;
+
+            self.commit_history(desc);
+        }
+
+        result
+    }
+
+    fn run_actions(
+        &mut self,
+        ctx: &Context,
+        frame: &mut Frame,
+        history_desc: &mut Option<String>,
+    ) -> Result<()> {
         while let Some(action) = self.actions.pop_front() {
             match action {
-                Action::AppQuit => frame.close(),
+                Action::AppQuit => {
+                    if self.prompt_unsaved_changes(|| Action::AppQuit) {
+                        continue;
+                    }
+
+                    self.is_close_confirmed = true;
+
+                    // an unanswered restore prompt keeps the recovery file for the next launch
+                    if !self.is_recovery_pending {
+                        remove_recovery(self.config.file_path.as_deref());
+                    }
+
+                    frame.close()
+                }
                 Action::AppTitleChanged(title) => {
                     let title = format!(
-                        "{} - {}",
+                        "{} - {}{}",
                         APP_TITLE,
+                        if self.is_dirty { "*" } else { "" },
                         title.unwrap_or("Unsaved Project".to_owned())
                     );
 
@@ -142,6 +241,8 @@ impl EditorApp {
                         }
 
                         self.associated_slots.clear();
+
+                        *history_desc = Some("Manage associated slots".to_owned());
                     }
                 }
                 Action::AssociatedSlotsEdit(id) => {
@@ -166,6 +267,9 @@ impl EditorApp {
                 Action::AssociatedSlotsUnselectAll => {
                     self.associated_slots.clear();
                 }
+                Action::AutosaveIntervalChanged(interval) => {
+                    self.storage.autosave_interval = interval;
+                }
                 Action::CandidateLower(slot_id, fragment_id) => {
                     let candidates = slot_id
                         .map(|id| self.ppd.get_slot_mut(id))
@@ -179,6 +283,10 @@ impl EditorApp {
 
                     if let Some(candidates) = candidates {
                         lower_in_vec(fragment_id, candidates);
+
+                        if self.is_slot_committable(slot_id) {
+                            *history_desc = Some("Reorder candidates".to_owned());
+                        }
                     }
                 }
                 Action::CandidateLowerBottom(slot_id, fragment_id) => {
@@ -194,6 +302,10 @@ impl EditorApp {
 
                     if let Some(candidates) = candidates {
                         lower_bottom_in_vec(fragment_id, candidates);
+
+                        if self.is_slot_committable(slot_id) {
+                            *history_desc = Some("Reorder candidates".to_owned());
+                        }
                     }
                 }
                 Action::CandidateRaise(slot_id, fragment_id) => {
@@ -209,6 +321,10 @@ impl EditorApp {
 
                     if let Some(candidates) = candidates {
                         raise_in_vec(fragment_id, candidates);
+
+                        if self.is_slot_committable(slot_id) {
+                            *history_desc = Some("Reorder candidates".to_owned());
+                        }
                     }
                 }
                 Action::CandidateRaiseTop(slot_id, fragment_id) => {
@@ -224,14 +340,49 @@ impl EditorApp {
 
                     if let Some(candidates) = candidates {
                         raise_top_in_vec(fragment_id, candidates);
+
+                        if self.is_slot_committable(slot_id) {
+                            *history_desc = Some("Reorder candidates".to_owned());
+                        }
                     }
                 }
+                Action::CanvasLayoutGridSize(value) => {
+                    self.config.canvas_layout_grid_size = value;
+                }
+                Action::CanvasShowLayoutGrid(value) => {
+                    self.config.canvas_show_layout_grid = value;
+                }
+                Action::CanvasShowPixelGrid(value) => {
+                    self.config.canvas_show_pixel_grid = value;
+                }
+                Action::CanvasShowRulers(value) => {
+                    self.config.canvas_show_rulers = value;
+                }
                 Action::CanvasShowSlotBoundaries(value) => {
                     self.config.canvas_show_slot_boundaries = value;
                 }
+                Action::CanvasSnapToGrid(value) => {
+                    self.config.canvas_snap_to_grid = value;
+                }
                 Action::CanvasStateChanged(state) => {
                     self.canvas_state = state;
                 }
+                Action::ChangesDiscard => {
+                    if let Some(action) = self.unsaved_changes_next_action.take() {
+                        self.is_changes_discarded = true;
+
+                        remove_recovery(self.config.file_path.as_deref());
+
+                        self.actions.push_back(action);
+                    }
+                }
+                Action::ChangesSave => {
+                    if self.file_save()? {
+                        if let Some(action) = self.unsaved_changes_next_action.take() {
+                            self.actions.push_back(action);
+                        }
+                    }
+                }
                 Action::CursorMoved(position) => {
                     self.cursor_position = position;
                 }
@@ -258,6 +409,27 @@ impl EditorApp {
                         }
                     }
                 }
+                Action::DollBackgroundReload(id) => {
+                    let Some(path) = self
+                        .ppd
+                        .get_doll(id)
+                        .filter(|doll| !doll.path.is_empty())
+                        .map(|doll| PathBuf::from(&doll.path))
+                    else {
+                        continue;
+                    };
+
+                    match self.reload_doll_image(id, &path, ctx) {
+                        Ok(_) => {
+                            *history_desc = Some("Reload doll background".to_owned());
+                        }
+                        Err(err) => {
+                            log::warn!("Reloading {:?} failed: {}", path, err);
+
+                            self.notify(format!("Failed to reload {}", path.display()), ctx);
+                        }
+                    }
+                }
                 Action::DollBackgroundRemove(id) => {
                     if let Some(doll) = self.ppd.get_doll_mut(id) {
                         self.textures_doll.remove(&id);
@@ -265,6 +437,8 @@ impl EditorApp {
                         doll.path = String::default();
 
                         doll.image = ImageData::default();
+
+                        *history_desc = Some("Remove doll background".to_owned());
                     }
                 }
                 Action::DollBackgroundUpload(id) => {
@@ -281,6 +455,8 @@ impl EditorApp {
                             doll.image.pixels = pixels;
 
                             self.textures_doll.insert(id, texture);
+
+                            *history_desc = Some("Change doll background".to_owned());
                         }
                     }
                 }
@@ -305,6 +481,12 @@ impl EditorApp {
                     }
                 }
                 Action::DollEditConfirm(id) => {
+                    *history_desc = Some(if id.is_none() {
+                        "Create doll".to_owned()
+                    } else {
+                        "Edit doll".to_owned()
+                    });
+
                     let id = id.or_else(|| self.ppd.add_doll().ok());
 
                     self.actived_doll = id;
@@ -342,6 +524,8 @@ impl EditorApp {
                     self.actived_doll = None;
 
                     self.ppd.remove_doll(id);
+
+                    *history_desc = Some("Delete doll".to_owned());
                 }
                 Action::DollRemoveRequest(id) => {
                     self.dialog_visible = true;
@@ -355,10 +539,64 @@ impl EditorApp {
                         if !doll.image.is_empty() {
                             doll.width = doll.image.width;
                             doll.height = doll.image.height;
+
+                            *history_desc = Some("Resize doll to background".to_owned());
                         }
                     }
                 }
+                Action::DuplicatesMerge(group) => {
+                    let removed = merge_duplicate_fragments(&mut self.ppd, &group);
+
+                    if removed > 0 {
+                        self.after_duplicates_merged(removed, ctx);
+
+                        *history_desc = Some("Merge duplicate fragments".to_owned());
+                    }
+                }
+                Action::DuplicatesMergeAll => {
+                    let groups = find_duplicate_fragments(&self.ppd);
+
+                    let removed: usize = groups
+                        .iter()
+                        .map(|group| merge_duplicate_fragments(&mut self.ppd, group))
+                        .sum();
+
+                    if removed > 0 {
+                        self.after_duplicates_merged(removed, ctx);
+
+                        *history_desc = Some("Merge duplicate fragments".to_owned());
+                    }
+                }
+                Action::ExportAtlas(include_dolls) => {
+                    let name = file_name_or(&self.ppd.meta.name, "Untitled");
+
+                    if let Some(path) = export_json(&format!("{}_atlas.json", name)) {
+                        export_atlas(&self.ppd, include_dolls, path)?;
+                    }
+                }
+                Action::ExportGodot(doll_id) => {
+                    let Some(doll) = self.ppd.get_doll(doll_id) else {
+                        continue;
+                    };
+
+                    let name = file_name_or(&doll.desc, "Doll");
+
+                    if let Some(path) = export_tscn(&format!("{}.tscn", name)) {
+                        export_godot(&self.ppd, doll_id, path)?;
+                    }
+                }
+                Action::ExportRenpy => {
+                    let name = file_name_or(&self.ppd.meta.name, "Untitled");
+
+                    if let Some(path) = export_rpy(&format!("{}.rpy", name.to_lowercase())) {
+                        export_renpy(&self.ppd, path)?;
+                    }
+                }
                 Action::FileNew => {
+                    if self.prompt_unsaved_changes(|| Action::FileNew) {
+                        continue;
+                    }
+
                     let mut ppd = PaperdollFactory::default();
 
                     if let Some(doll) = ppd.get_doll_mut(0) {
@@ -373,6 +611,10 @@ impl EditorApp {
                     self.config.file_path = None;
                 }
                 Action::FileOpen => {
+                    if self.prompt_unsaved_changes(|| Action::FileOpen) {
+                        continue;
+                    }
+
                     if let Some(path) = select_file() {
                         self.load_ppd_from_path(&path)?;
 
@@ -384,6 +626,10 @@ impl EditorApp {
                     }
                 }
                 Action::FileOpenPath(path) => {
+                    if self.prompt_unsaved_changes(|| Action::FileOpenPath(path.clone())) {
+                        continue;
+                    }
+
                     self.load_ppd_from_path(&path)?;
 
                     self.actions.push_back(Action::AppTitleChanged(Some(
@@ -392,32 +638,50 @@ impl EditorApp {
 
                     self.storage.recent_files.push(path);
                 }
-                Action::FileSave => {
-                    let newly_save = self.config.file_path.is_none();
+                Action::FilePack => {
+                    if self.prompt_unsaved_changes(|| Action::FilePack) {
+                        continue;
+                    }
 
-                    if let Some(path) = self.config.file_path.clone().or_else(|| {
-                        let name = (!self.ppd.meta.name.is_empty())
-                            .then_some(self.ppd.meta.name.as_str())
-                            .unwrap_or("Untitled");
+                    let Some(dir) = select_folder() else {
+                        continue;
+                    };
 
-                        let filename = format!("{}.{}", name.replace(" ", "_"), EXTENSION_NAME);
+                    let mut manifest = pack_folder(&dir)?;
 
-                        create_file(&filename)
-                    }) {
-                        self.file_save_to_path(&path)?;
-                        if newly_save {
-                            self.storage.recent_files.push(path);
-                        }
-                    }
-                }
-                Action::FileSaveAs => {
-                    let name = (!self.ppd.meta.name.is_empty())
-                        .then_some(self.ppd.meta.name.as_str())
-                        .unwrap_or("Untitled");
+                    let name = dir
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or("Untitled".to_owned());
 
                     if let Some(path) =
                         create_file(&format!("{}.{}", name.replace(" ", "_"), EXTENSION_NAME))
                     {
+                        paperdoll_tar::save(&mut manifest, &path)?;
+
+                        self.load_ppd_from_path(&path)?;
+
+                        self.actions.push_back(Action::AppTitleChanged(Some(
+                            path.to_string_lossy().to_string(),
+                        )));
+
+                        self.storage.recent_files.push(path);
+                    }
+                }
+                Action::FileSave => {
+                    self.file_save()?;
+                }
+                Action::FileUnpack => {
+                    if let Some(dir) = select_folder() {
+                        self.commit_pending_history_now();
+
+                        unpack_to_folder(&self.ppd, dir)?;
+                    }
+                }
+                Action::FileSaveAs => {
+                    let name = file_name_or(&self.ppd.meta.name, "Untitled");
+
+                    if let Some(path) = create_file(&format!("{}.{}", name, EXTENSION_NAME)) {
                         self.file_save_to_path(&path)?;
                         self.storage.recent_files.push(path);
                     }
@@ -462,25 +726,117 @@ impl EditorApp {
                     self.actions.push_back(Action::WindowFragmentVisible(true));
                 }
                 Action::FragmentCreateFromBatchImages => {
+                    let Some(paths) = select_textures() else {
+                        continue;
+                    };
+
+                    // decoded images are passed on so that they are not decoded again
+                    let mut images: Vec<(PathBuf, ImageData)> = vec![];
+                    let mut unique_indices: Vec<usize> = vec![];
+                    let mut duplicates = vec![];
+
+                    // images are checked against existing fragments and the ones selected before
+                    for path in paths {
+                        let Ok(image) = open_image_rgba(&path) else {
+                            continue;
+                        };
+
+                        let file_name = path
+                            .file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                            .to_string();
+
+                        if let Some(id) = find_fragment_with_image(&self.ppd, &image) {
+                            duplicates.push(format!("{} (same as fragment {})", file_name, id));
+                        } else if unique_indices
+                            .iter()
+                            .any(|index| is_same_image(&images[*index].1, &image))
+                        {
+                            duplicates.push(format!("{} (selected twice)", file_name));
+                        } else {
+                            unique_indices.push(images.len());
+                        }
+
+                        images.push((path, image));
+                    }
+
+                    if duplicates.is_empty() {
+                        self.actions
+                            .push_back(Action::FragmentCreateFromImages(images));
+
+                        continue;
+                    }
+
+                    let unique_images = unique_indices
+                        .into_iter()
+                        .map(|index| {
+                            let (path, image) = &images[index];
+
+                            (
+                                path.clone(),
+                                ImageData {
+                                    width: image.width,
+                                    height: image.height,
+                                    color_type: ColorType::Rgba,
+                                    pixels: image.pixels.clone(),
+                                },
+                            )
+                        })
+                        .collect();
+
+                    self.dialog_visible = true;
+
+                    self.dialog_option = DialogOption::default()
+                        .text(format!(
+                            "{} of the selected images duplicate other fragments:\n\n{}",
+                            duplicates.len(),
+                            duplicates.join("\n")
+                        ))
+                        .primary_text("Import All")
+                        .primary_action(Action::FragmentCreateFromImages(images))
+                        .secondary_text("Skip Duplicates")
+                        .secondary_action(Action::FragmentCreateFromImages(unique_images))
+                        .tertiary_text("Cancel");
+                }
+                Action::FragmentCreateFromImages(images) => {
                     self.actived_fragment = None;
 
-                    if let Some(paths) = select_textures() {
-                        for path in paths {
-                            if let Ok(image) = open_image_rgba(&path) {
-                                let texture = upload_image_to_texture(&image, "fragment", ctx);
+                    for (path, image) in images {
+                        let texture = upload_image_to_texture(&image, "fragment", ctx);
 
-                                if let Ok(id) = self.ppd.add_fragment() {
-                                    self.actions.push_back(Action::FragmentUpdateTexture(
-                                        id,
-                                        path,
-                                        texture,
-                                        image.pixels,
-                                    ));
-                                }
-                            }
+                        if let Ok(id) = self.ppd.add_fragment() {
+                            self.actions.push_back(Action::FragmentUpdateTexture(
+                                id,
+                                path,
+                                texture,
+                                image.pixels,
+                            ));
+
+                            *history_desc = Some("Add fragments from images".to_owned());
                         }
                     }
                 }
+                Action::FragmentCreateFromOra(create_slots) => {
+                    self.actived_fragment = None;
+
+                    let Some(path) = select_ora() else {
+                        continue;
+                    };
+
+                    let ora = OraFile::open(&path)?;
+
+                    let (groups, layers) = if create_slots {
+                        ora.into_groups()
+                    } else {
+                        (vec![], ora.into_layers())
+                    };
+
+                    // layers imported before a failure are kept, so they are committed as well
+                    *history_desc = Some("Import OpenRaster layers".to_owned());
+
+                    self.import_ora_layers(groups, layers, ctx)?;
+                }
                 Action::FragmentEdit(id) => {
                     if let Some(fragment) = self.ppd.get_fragment(id) {
                         self.actived_fragment = Some(id);
@@ -542,6 +898,12 @@ impl EditorApp {
                         }
                     }
 
+                    *history_desc = Some(if is_create_mode {
+                        "Create fragment".to_owned()
+                    } else {
+                        "Edit fragment".to_owned()
+                    });
+
                     let id = id.or_else(|| self.ppd.add_fragment().ok());
 
                     self.actived_fragment = id;
@@ -576,10 +938,33 @@ impl EditorApp {
 
                     self.window_fragment_error = None;
                 }
+                Action::FragmentReload(id) => {
+                    let Some(path) = self
+                        .ppd
+                        .get_fragment(id)
+                        .filter(|fragment| !fragment.path.is_empty())
+                        .map(|fragment| PathBuf::from(&fragment.path))
+                    else {
+                        continue;
+                    };
+
+                    match self.reload_fragment_image(id, &path, ctx) {
+                        Ok(_) => {
+                            *history_desc = Some("Reload fragment".to_owned());
+                        }
+                        Err(err) => {
+                            log::warn!("Reloading {:?} failed: {}", path, err);
+
+                            self.notify(format!("Failed to reload {}", path.display()), ctx);
+                        }
+                    }
+                }
                 Action::FragmentRemoveConfirm(id) => {
                     self.actived_fragment = None;
 
                     self.ppd.remove_fragment(id);
+
+                    *history_desc = Some("Delete fragment".to_owned());
                 }
                 Action::FragmentRemoveRequest(id) => {
                     self.dialog_visible = true;
@@ -611,6 +996,100 @@ impl EditorApp {
                         fragment.image.height = texture.height;
                         fragment.image.color_type = ColorType::Rgba;
                         fragment.image.pixels = pixels;
+
+                        if !self.window_fragment_visible && history_desc.is_none() {
+                            *history_desc = Some("Change fragment image".to_owned());
+                        }
+                    }
+                }
+                Action::GuideAdd(doll_id, orientation) => {
+                    if let Some(doll) = self.ppd.get_doll(doll_id) {
+                        let value = match orientation {
+                            GuideOrientation::Horizontal => doll.height / 2,
+                            GuideOrientation::Vertical => doll.width / 2,
+                        };
+
+                        self.guides
+                            .doll_mut(doll_id)
+                            .get_mut(orientation)
+                            .push(value as f32);
+
+                        self.set_dirty(true);
+                    }
+                }
+                Action::GuideRemove(doll_id, orientation, index) => {
+                    let guides = self.guides.doll_mut(doll_id).get_mut(orientation);
+
+                    if index < guides.len() {
+                        guides.remove(index);
+
+                        self.set_dirty(true);
+                    }
+                }
+                Action::GuidesChanged => {
+                    self.set_dirty(true);
+                }
+                Action::HistoryRedo => {
+                    self.commit_pending_history_now();
+
+                    let slots = self.ppd.slots().map(|(id, _)| *id).collect();
+
+                    if let Some(changed) = self.history.redo(&mut self.ppd)? {
+                        self.restore_from_history(slots, changed, ctx);
+
+                        self.set_dirty(!self.history.is_saved());
+                    }
+                }
+                Action::HistoryUndo => {
+                    self.commit_pending_history_now();
+
+                    let slots = self.ppd.slots().map(|(id, _)| *id).collect();
+
+                    if let Some(changed) = self.history.undo(&mut self.ppd)? {
+                        self.restore_from_history(slots, changed, ctx);
+
+                        self.set_dirty(!self.history.is_saved());
+                    }
+                }
+                Action::ImagesReload => {
+                    let paths: HashSet<PathBuf> = self
+                        .ppd
+                        .dolls()
+                        .map(|(_, doll)| doll.path.as_str())
+                        .chain(
+                            self.ppd
+                                .fragments()
+                                .map(|(_, fragment)| fragment.path.as_str()),
+                        )
+                        .filter(|path| !path.is_empty())
+                        .map(PathBuf::from)
+                        .collect();
+
+                    let mut reloaded = 0;
+                    let mut failed = 0;
+
+                    for path in paths {
+                        match self.reload_images_from_path(&path, ctx) {
+                            Ok(count) => reloaded += count,
+                            Err(err) => {
+                                log::warn!("Reloading {:?} failed: {}", path, err);
+
+                                failed += 1;
+                            }
+                        }
+                    }
+
+                    if reloaded > 0 {
+                        *history_desc = Some("Reload images".to_owned());
+                    }
+
+                    if failed > 0 {
+                        self.notify(
+                            format!("Reloaded {} images, {} failed", reloaded, failed),
+                            ctx,
+                        );
+                    } else {
+                        self.notify(format!("Reloaded {} images", reloaded), ctx);
                     }
                 }
                 Action::OpenViewer => {
@@ -631,9 +1110,15 @@ impl EditorApp {
                 Action::PpdLoad(ppd) => {
                     self.ppd = ppd;
 
+                    self.is_dirty = false;
+
                     self.actions.push_back(Action::PpdChanged);
                 }
                 Action::PpdLoadExample(example) => {
+                    if self.prompt_unsaved_changes(|| Action::PpdLoadExample(example)) {
+                        continue;
+                    }
+
                     let ppd = paperdoll_tar::read(example.data())?;
 
                     self.actions.push_back(Action::PpdLoad(ppd));
@@ -655,12 +1140,16 @@ impl EditorApp {
                     self.canvas_state = CanvasState::default();
                     self.canvas_original_pos_anchor = None;
                     self.canvas_original_pos_slot_and_drag_offset = None;
+                    self.canvas_selection_origin = None;
                     self.fragments_filter_keyword = String::default();
                     self.align_basis_slots.clear();
                     self.associated_slots.clear();
                     self.locked_slots.clear();
+                    self.selected_slots.clear();
                     self.visible_slots = ppd.slots().map(|(id, _)| *id).collect();
-                    self.slot_copy = None;
+                    self.slot_copy.clear();
+
+                    self.image_watcher.reset();
 
                     self.adapter_doll = None;
                     self.adapter_fragment = None;
@@ -673,19 +1162,137 @@ impl EditorApp {
                     self.dialog_visible = false;
                     self.dialog_option = DialogOption::default();
 
+                    self.is_recovery_pending = false;
+
                     self.window_doll_error = None;
                     self.window_fragment_error = None;
                     self.window_slot_error = None;
 
+                    self.history.reset(ppd);
+                    if !self.is_dirty {
+                        self.history.mark_saved();
+                    }
+                    self.history_pending = None;
+                    *history_desc = None;
+
+                    self.problems = validate(ppd);
+
+                    self.canvas_dragging_guide = None;
+
+                    self.guides = match &self.config.file_path {
+                        Some(path) => Guides::load(path).unwrap_or_else(|err| {
+                            log::warn!("Loading guides failed: {}", err);
+
+                            Guides::default()
+                        }),
+                        None => Guides::default(),
+                    };
+
                     self.actions
                         .push_back(Action::WindowAssociatedSlotsVisible(false));
                     self.actions.push_back(Action::WindowDollVisible(false));
                     self.actions.push_back(Action::WindowFragmentVisible(false));
                     self.actions.push_back(Action::WindowSlotVisible(false));
                 }
+                Action::ProblemSelect(target) => match target {
+                    ProblemTarget::Doll(id) => {
+                        self.actived_doll = Some(id);
+                    }
+                    ProblemTarget::Fragment(id) => {
+                        self.actived_fragment = Some(id);
+                    }
+                    ProblemTarget::Slot(id) => {
+                        let is_in_actived_doll = self
+                            .actived_doll
+                            .and_then(|doll_id| self.ppd.get_doll(doll_id))
+                            .map_or(false, |doll| doll.slots.contains(&id));
+
+                        if !is_in_actived_doll {
+                            if let Some((doll_id, _)) =
+                                self.ppd.dolls().find(|(_, doll)| doll.slots.contains(&id))
+                            {
+                                self.actived_doll = Some(*doll_id);
+                            }
+                        }
+
+                        self.actived_slot = Some(id);
+                    }
+                },
                 Action::RecentFilesClean => {
                     self.storage.recent_files.clear();
                 }
+                Action::RecoveryCheck => {
+                    if let Some(path) = find_recovery(self.config.file_path.as_deref()) {
+                        self.is_recovery_pending = true;
+
+                        self.dialog_visible = true;
+
+                        self.dialog_option = DialogOption::default()
+                            .text("A recovered version of this project was found. Do you want to restore it?")
+                            .primary_text("Restore")
+                            .primary_action(Action::RecoveryRestore(path.clone()))
+                            .secondary_text("Discard")
+                            .secondary_action(Action::RecoveryDiscard(path));
+                    }
+                }
+                Action::RecoveryDiscard(path) => {
+                    self.is_recovery_pending = false;
+
+                    fs::remove_file(path)?;
+                }
+                Action::RecoveryRestore(path) => {
+                    self.is_recovery_pending = false;
+
+                    self.ppd = paperdoll_tar::load(&path)?;
+
+                    self.actions.push_back(Action::PpdChanged);
+
+                    self.set_dirty(true);
+                }
+                Action::RelinkConfirm => {
+                    let entries = mem::take(&mut self.relink_entries);
+
+                    let mut relinked = 0;
+
+                    for entry in entries {
+                        let Some(path) = entry.new_path else {
+                            continue;
+                        };
+
+                        let result = match entry.target {
+                            RelinkTarget::Doll(id) => self.reload_doll_image(id, &path, ctx),
+                            RelinkTarget::Fragment(id) => {
+                                self.reload_fragment_image(id, &path, ctx)
+                            }
+                        };
+
+                        match result {
+                            Ok(_) => relinked += 1,
+                            Err(err) => log::warn!("Relinking {:?} failed: {}", path, err),
+                        }
+                    }
+
+                    if relinked > 0 {
+                        *history_desc = Some("Relink images".to_owned());
+
+                        self.notify(format!("Relinked {} images", relinked), ctx);
+                    }
+
+                    self.relink_root = None;
+
+                    self.window_relink_visible = false;
+                }
+                Action::RelinkSelectRoot => {
+                    let Some(dir) = select_folder() else {
+                        continue;
+                    };
+
+                    self.relink_entries = find_relinks(&self.ppd, &dir)?;
+
+                    self.relink_root = Some(dir);
+
+                    self.window_relink_visible = true;
+                }
                 Action::SlotAdapterFragmentFilter => {
                     self.filter_slot_fragment();
                 }
@@ -702,6 +1309,10 @@ impl EditorApp {
 
                     if let Some(candidates) = candidates {
                         candidates.push(fragment_id);
+
+                        if self.is_slot_committable(slot_id) {
+                            *history_desc = Some("Add candidate".to_owned());
+                        }
                     }
                 }
                 Action::SlotAddCandidates(slot_id, fragments) => {
@@ -723,6 +1334,10 @@ impl EditorApp {
 
                             candidates.push(fragment_id);
                         }
+
+                        if self.is_slot_committable(slot_id) {
+                            *history_desc = Some("Add candidates".to_owned());
+                        }
                     }
                 }
                 Action::SlotAddPosition(id) => {
@@ -738,10 +1353,21 @@ impl EditorApp {
 
                     if let Some(positions) = positions {
                         positions.push(Point::default());
+
+                        if self.is_slot_committable(id) {
+                            *history_desc = Some("Add slot position".to_owned());
+                        }
+                    }
+                }
+                Action::SlotAlign(align) => {
+                    if self.align_slots(align) {
+                        *history_desc = Some(align.description().to_owned());
                     }
                 }
                 Action::SlotCopy(id) => {
-                    self.slot_copy = Some(id);
+                    let slots = self.selected_slot_ids();
+
+                    self.slot_copy = if slots.contains(&id) { slots } else { vec![id] };
                 }
                 Action::SlotCreate => {
                     self.actived_slot = None;
@@ -752,6 +1378,11 @@ impl EditorApp {
 
                     self.actions.push_back(Action::WindowSlotVisible(true));
                 }
+                Action::SlotDistribute(distribute) => {
+                    if self.distribute_slots(distribute) {
+                        *history_desc = Some(distribute.description().to_owned());
+                    }
+                }
                 Action::SlotDuplicate(doll_id, slot_id) => {
                     self.actions.push_back(Action::SlotCopy(slot_id));
 
@@ -793,6 +1424,12 @@ impl EditorApp {
                 Action::SlotEditConfirm(id) => {
                     let is_create_mode = id.is_none();
 
+                    *history_desc = Some(if is_create_mode {
+                        "Create slot".to_owned()
+                    } else {
+                        "Edit slot".to_owned()
+                    });
+
                     let id = id.or_else(|| self.ppd.add_slot().ok());
 
                     self.actived_slot = id;
@@ -827,67 +1464,95 @@ impl EditorApp {
                 Action::SlotLower(doll_id, slot_id) => {
                     if let Some(doll) = self.ppd.get_doll_mut(doll_id) {
                         lower_in_vec(slot_id, &mut doll.slots);
+
+                        *history_desc = Some("Lower slot".to_owned());
                     }
                 }
                 Action::SlotLowerBottom(doll_id, slot_id) => {
                     if let Some(doll) = self.ppd.get_doll_mut(doll_id) {
                         lower_bottom_in_vec(slot_id, &mut doll.slots);
+
+                        *history_desc = Some("Lower slot to the bottom".to_owned());
                     }
                 }
                 Action::SlotPaste(doll_id) => {
-                    if self.slot_copy.is_none() {
+                    if self.slot_copy.is_empty() || self.ppd.get_doll(doll_id).is_none() {
                         continue;
                     }
 
-                    let slot_copy = self.slot_copy.unwrap();
+                    let slot_copy = mem::take(&mut self.slot_copy);
 
-                    if self.ppd.get_doll(doll_id).is_none()
-                        || self.ppd.get_slot(slot_copy).is_none()
-                    {
+                    let mut pasted_slots = vec![];
+
+                    for copy_id in slot_copy {
+                        let Some(slot_copy) = self.ppd.get_slot(copy_id) else {
+                            continue;
+                        };
+
+                        let desc = slot_copy.desc.clone();
+                        let required = slot_copy.required;
+                        let constrainted = slot_copy.constrainted;
+                        let positions = slot_copy.positions.clone();
+                        let width = slot_copy.width;
+                        let height = slot_copy.height;
+                        let anchor = slot_copy.anchor;
+                        let candidates = slot_copy.candidates.clone();
+
+                        let id = self.ppd.add_slot()?;
+
+                        if let Some(slot) = self.ppd.get_slot_mut(id) {
+                            slot.desc = desc.clone();
+                            slot.required = required;
+                            slot.constrainted = constrainted;
+                            slot.positions = positions;
+                            slot.width = width;
+                            slot.height = height;
+                            slot.anchor = anchor;
+                            slot.candidates = candidates;
+
+                            if let Some(doll) = self.ppd.get_doll_mut(doll_id) {
+                                doll.slots.push(id);
+                            }
+
+                            self.visible_slots.insert(id);
+
+                            pasted_slots.push(id);
+                        }
+                    }
+
+                    if pasted_slots.is_empty() {
                         continue;
                     }
 
-                    let id = self.ppd.add_slot()?;
-
-                    self.actived_slot = Some(id);
-
-                    let slot_copy = self.ppd.get_slot(slot_copy).unwrap();
-                    let desc = slot_copy.desc.clone();
-                    let required = slot_copy.required;
-                    let constrainted = slot_copy.constrainted;
-                    let positions = slot_copy.positions.clone();
-                    let width = slot_copy.width;
-                    let height = slot_copy.height;
-                    let anchor = slot_copy.anchor;
-                    let candidates = slot_copy.candidates.clone();
-
-                    if let Some(slot) = self.ppd.get_slot_mut(id) {
-                        slot.desc = desc.clone();
-                        slot.required = required;
-                        slot.constrainted = constrainted;
-                        slot.positions = positions;
-                        slot.width = width;
-                        slot.height = height;
-                        slot.anchor = anchor;
-                        slot.candidates = candidates;
-
-                        if let Some(doll) = self.ppd.get_doll_mut(doll_id) {
-                            doll.slots.push(id);
-                        }
+                    self.actived_slot = pasted_slots.last().copied();
 
-                        self.visible_slots.insert(id);
+                    self.selected_slots = if pasted_slots.len() > 1 {
+                        pasted_slots.iter().copied().collect()
+                    } else {
+                        HashSet::default()
+                    };
 
-                        self.slot_copy = None;
-                    }
+                    *history_desc = Some(
+                        if pasted_slots.len() > 1 {
+                            "Paste slots"
+                        } else {
+                            "Paste slot"
+                        }
+                        .to_owned(),
+                    );
                 }
                 Action::SlotRaise(doll_id, slot_id) => {
                     if let Some(doll) = self.ppd.get_doll_mut(doll_id) {
                         raise_in_vec(slot_id, &mut doll.slots);
+
+                        *history_desc = Some("Raise slot".to_owned());
                     }
                 }
                 Action::SlotRaiseTop(doll_id, slot_id) => {
                     if let Some(doll) = self.ppd.get_doll_mut(doll_id) {
                         raise_top_in_vec(slot_id, &mut doll.slots);
+
+                        *history_desc = Some("Raise slot to the top".to_owned());
                     }
                 }
                 Action::SlotRemoveCandidate(slot_id, fragment_id) => {
@@ -905,6 +1570,10 @@ impl EditorApp {
                         if let Some(position) = candidates.iter().position(|v| *v == fragment_id) {
                             candidates.remove(position);
                         }
+
+                        if self.is_slot_committable(slot_id) {
+                            *history_desc = Some("Remove candidate".to_owned());
+                        }
                     }
                 }
                 Action::SlotRemoveCandidates(slot_id, fragments) => {
@@ -926,6 +1595,10 @@ impl EditorApp {
                                 candidates.remove(position);
                             }
                         }
+
+                        if self.is_slot_committable(slot_id) {
+                            *history_desc = Some("Remove candidates".to_owned());
+                        }
                     }
                 }
                 Action::SlotRemoveConfirm(id) => {
@@ -933,7 +1606,12 @@ impl EditorApp {
 
                     self.ppd.remove_slot(id);
 
+                    self.align_basis_slots.remove(&id);
+                    self.locked_slots.remove(&id);
+                    self.selected_slots.remove(&id);
                     self.visible_slots.remove(&id);
+
+                    *history_desc = Some("Delete slot".to_owned());
                 }
                 Action::SlotRemovePosition(id, index) => {
                     let positions = id
@@ -948,15 +1626,47 @@ impl EditorApp {
 
                     if let Some(positions) = positions {
                         positions.remove(index);
+
+                        if self.is_slot_committable(id) {
+                            *history_desc = Some("Remove slot position".to_owned());
+                        }
                     }
                 }
                 Action::SlotRemoveRequest(id) => {
+                    let slots = self.selected_slot_ids();
+
+                    if slots.len() > 1 && slots.contains(&id) {
+                        self.dialog_visible = true;
+
+                        self.dialog_option = DialogOption::confirm(&format!(
+                            "Are you sure to delete {} selected slots?",
+                            slots.len()
+                        ))
+                        .primary_action(Action::SlotRemoveSelectedConfirm(slots));
+
+                        continue;
+                    }
+
                     self.dialog_visible = true;
 
                     self.dialog_option =
                         DialogOption::confirm(&format!("Are you sure to delete slot {}?", id))
                             .primary_action(Action::SlotRemoveConfirm(id));
                 }
+                Action::SlotRemoveSelectedConfirm(slots) => {
+                    self.actived_slot = None;
+
+                    for id in slots {
+                        self.ppd.remove_slot(id);
+
+                        self.align_basis_slots.remove(&id);
+                        self.locked_slots.remove(&id);
+                        self.selected_slots.remove(&id);
+                        self.visible_slots.remove(&id);
+                    }
+
+                    *history_desc = Some("Delete slots".to_owned());
+                }
                 Action::ViewportCenter => {
                     self.viewport.offset = Vec2::ZERO;
                 }
@@ -982,6 +1692,32 @@ impl EditorApp {
                         self.viewport.scale = scale;
                     }
                 }
+                Action::WatchImages(value) => {
+                    self.storage.watch_images = value;
+
+                    self.image_watcher.reset();
+                }
+                Action::WatchedImageChanged(path) => {
+                    let file_name = path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+
+                    match self.reload_images_from_path(&path, ctx) {
+                        Ok(0) => {}
+                        Ok(_) => {
+                            self.notify(format!("Reloaded {}", file_name), ctx);
+
+                            *history_desc = Some(format!("Reload {}", file_name));
+                        }
+                        Err(err) => {
+                            log::warn!("Reloading {:?} failed: {}", path, err);
+
+                            self.notify(format!("Failed to reload {}", file_name), ctx);
+                        }
+                    }
+                }
                 Action::WindowAboutVisible(visible) => {
                     self.window_about_visible = visible;
                 }
@@ -999,6 +1735,13 @@ impl EditorApp {
                         self.window_doll_error = None;
                     }
                 }
+                Action::WindowDuplicatesVisible(visible) => {
+                    self.window_duplicates_visible = visible;
+
+                    if visible {
+                        self.duplicate_fragments = find_duplicate_fragments(&self.ppd);
+                    }
+                }
                 Action::WindowFragmentVisible(visible) => {
                     if !visible && self.window_fragment_error.is_some() {
                         continue;
@@ -1010,6 +1753,24 @@ impl EditorApp {
                         self.window_fragment_error = None;
                     }
                 }
+                Action::WindowGuidesVisible(visible) => {
+                    self.window_guides_visible = visible;
+                }
+                Action::WindowHistoryVisible(visible) => {
+                    self.window_history_visible = visible;
+                }
+                Action::WindowProblemsVisible(visible) => {
+                    self.window_problems_visible = visible;
+                }
+                Action::WindowRelinkVisible(visible) => {
+                    self.window_relink_visible = visible;
+
+                    if !visible {
+                        self.relink_entries.clear();
+
+                        self.relink_root = None;
+                    }
+                }
                 Action::WindowSlotVisible(visible) => {
                     if !visible && self.window_slot_error.is_some() {
                         continue;
@@ -1027,12 +1788,148 @@ impl EditorApp {
         Ok(())
     }
 
+    pub(super) fn commit_pending_history(&mut self, ctx: &Context) {
+        if self.history_pending.is_none() {
+            return;
+        }
+
+        let is_pointer_down = ctx.input(|i| i.pointer.any_down());
+        let has_focus = ctx.memory(|mem| mem.focus().is_some());
+
+        if !is_pointer_down && !has_focus {
+            self.commit_pending_history_now();
+        }
+    }
+
+    fn after_duplicates_merged(&mut self, removed: usize, ctx: &Context) {
+        if self
+            .actived_fragment
+            .map_or(false, |id| self.ppd.get_fragment(id).is_none())
+        {
+            self.actived_fragment = None;
+        }
+
+        self.duplicate_fragments = find_duplicate_fragments(&self.ppd);
+
+        self.notify(format!("Removed {} duplicate fragments", removed), ctx);
+    }
+
+    fn commit_history(&mut self, desc: impl Into<String>) {
+        self.history.commit(&self.ppd, desc);
+
+        self.problems = validate(&self.ppd);
+
+        self.set_dirty(true);
+    }
+
+    fn add_fragment_from_ora_layer(&mut self, layer: &OraLayer, ctx: &Context) -> Result<u32> {
+        let image = layer.to_image_data();
+
+        let texture = upload_image_to_texture(&image, "fragment", ctx);
+
+        let id = self.ppd.add_fragment()?;
+
+        // the layer has no image file of its own, so the path is left empty
+        // and the fragment is skipped when watching, reloading or relinking images
+        if let Some(fragment) = self.ppd.get_fragment_mut(id) {
+            fragment.desc = layer.name.clone();
+            fragment.image = image;
+        }
+
+        self.textures_fragment.insert(id, texture);
+
+        Ok(id)
+    }
+
+    fn import_ora_layers(
+        &mut self,
+        groups: Vec<OraGroup>,
+        layers: Vec<OraLayer>,
+        ctx: &Context,
+    ) -> Result<()> {
+        for layer in &layers {
+            self.add_fragment_from_ora_layer(layer, ctx)?;
+        }
+
+        // groups are listed from top to bottom while slots of a doll are not
+        for group in groups.into_iter().rev() {
+            let mut candidates = vec![];
+
+            for layer in &group.layers {
+                candidates.push(self.add_fragment_from_ora_layer(layer, ctx)?);
+            }
+
+            let id = self.ppd.add_slot()?;
+
+            if let Some(slot) = self.ppd.get_slot_mut(id) {
+                slot.desc = group.name;
+                slot.positions = vec![Point {
+                    x: group.x as f32,
+                    y: group.y as f32,
+                }];
+                slot.width = group.width;
+                slot.height = group.height;
+                slot.candidates = candidates;
+            }
+
+            if let Some(doll_id) = self.actived_doll {
+                if let Some(doll) = self.ppd.get_doll_mut(doll_id) {
+                    doll.slots.push(id);
+                }
+            }
+
+            self.visible_slots.insert(id);
+        }
+
+        Ok(())
+    }
+
+    fn commit_pending_history_now(&mut self) {
+        if let Some(desc) = self.history_pending.take() {
+            self.commit_history(desc);
+        }
+    }
+
+    fn file_save(&mut self) -> Result<bool> {
+        let newly_save = self.config.file_path.is_none();
+
+        if let Some(path) = self.config.file_path.clone().or_else(|| {
+            let name = file_name_or(&self.ppd.meta.name, "Untitled");
+
+            let filename = format!("{}.{}", name, EXTENSION_NAME);
+
+            create_file(&filename)
+        }) {
+            self.file_save_to_path(&path)?;
+            if newly_save {
+                self.storage.recent_files.push(path);
+            }
+
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
     fn file_save_to_path<P>(&mut self, path: P) -> Result<()>
     where
         P: AsRef<Path>,
     {
+        self.commit_pending_history_now();
+
         paperdoll_tar::save(&mut self.ppd.to_manifest(), &path)?;
 
+        if let Err(err) = self.guides.save(&path) {
+            log::warn!("Saving guides failed: {}", err);
+        }
+
+        remove_recovery(self.config.file_path.as_deref());
+        remove_recovery(Some(path.as_ref()));
+
+        self.history.mark_saved();
+
+        self.is_dirty = false;
+
         self.actions.push_back(Action::AppTitleChanged(Some(
             path.as_ref().to_string_lossy().to_string(),
         )));
@@ -1063,6 +1960,11 @@ impl EditorApp {
         }
     }
 
+    fn is_slot_committable(&self, slot_id: Option<u32>) -> bool {
+        // changes made in the slot window are committed when the window is confirmed
+        slot_id.is_some() && !self.window_slot_visible
+    }
+
     fn load_ppd_from_path<P>(&mut self, path: P) -> Result<()>
     where
         P: AsRef<Path>,
@@ -1075,6 +1977,124 @@ impl EditorApp {
         Ok(())
     }
 
+    fn prompt_unsaved_changes(&mut self, next_action: impl FnOnce() -> Action) -> bool {
+        if !self.is_dirty || self.is_changes_discarded {
+            self.is_changes_discarded = false;
+
+            return false;
+        }
+
+        self.unsaved_changes_next_action = Some(next_action());
+
+        self.dialog_visible = true;
+
+        self.dialog_option = DialogOption::default()
+            .text("Do you want to save changes to this project?")
+            .primary_text("Save")
+            .primary_action(Action::ChangesSave)
+            .secondary_text("Discard")
+            .secondary_action(Action::ChangesDiscard)
+            .tertiary_text("Cancel");
+
+        true
+    }
+
+    fn restore_from_history(
+        &mut self,
+        previous_slots: HashSet<u32>,
+        changed: ChangedImages,
+        ctx: &Context,
+    ) {
+        for id in changed.dolls {
+            match self.ppd.get_doll(id) {
+                Some(doll) if !doll.image.is_empty() => {
+                    let texture = upload_image_to_texture(&doll.image, "doll", ctx);
+
+                    self.textures_doll.insert(id, texture);
+                }
+                _ => {
+                    self.textures_doll.remove(&id);
+                }
+            }
+        }
+
+        for id in changed.fragments {
+            match self.ppd.get_fragment(id) {
+                Some(fragment) if !fragment.image.is_empty() => {
+                    let texture = upload_image_to_texture(&fragment.image, "fragment", ctx);
+
+                    self.textures_fragment.insert(id, texture);
+                }
+                _ => {
+                    self.textures_fragment.remove(&id);
+                }
+            }
+        }
+
+        // textures of removed dolls and fragments are dropped
+        let ppd = &self.ppd;
+
+        self.textures_doll
+            .retain(|id, _| ppd.get_doll(*id).is_some());
+        self.textures_fragment
+            .retain(|id, _| ppd.get_fragment(*id).is_some());
+
+        self.problems = validate(&self.ppd);
+
+        if self.window_duplicates_visible {
+            self.duplicate_fragments = find_duplicate_fragments(&self.ppd);
+        }
+
+        let slots: HashSet<u32> = self.ppd.slots().map(|(id, _)| *id).collect();
+
+        for id in slots.difference(&previous_slots) {
+            self.visible_slots.insert(*id);
+        }
+
+        self.align_basis_slots.retain(|id| slots.contains(id));
+        self.associated_slots.retain(|id| slots.contains(id));
+        self.locked_slots.retain(|id| slots.contains(id));
+        self.selected_slots.retain(|id| slots.contains(id));
+        self.visible_slots.retain(|id| slots.contains(id));
+        self.slot_copy.retain(|id| slots.contains(id));
+
+        if self
+            .actived_doll
+            .map_or(true, |id| self.ppd.get_doll(id).is_none())
+        {
+            self.actived_doll = self.ppd.dolls().nth(0).map(|(id, _)| *id);
+        }
+
+        if self
+            .actived_fragment
+            .map_or(false, |id| self.ppd.get_fragment(id).is_none())
+        {
+            self.actived_fragment = None;
+        }
+
+        if self
+            .actived_slot
+            .map_or(false, |id| self.ppd.get_slot(id).is_none())
+        {
+            self.actived_slot = None;
+        }
+    }
+
+    fn set_dirty(&mut self, is_dirty: bool) {
+        if self.is_dirty == is_dirty {
+            return;
+        }
+
+        self.is_dirty = is_dirty;
+
+        self.actions.push_back(Action::AppTitleChanged(
+            self.config
+                .file_path
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string()),
+        ));
+    }
+
     fn upload_texture(
         &mut self,
         name: impl Into<String>,
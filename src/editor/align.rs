@@ -0,0 +1,190 @@
+use eframe::epaint::{pos2, vec2, Rect, Vec2};
+
+use super::EditorApp;
+
+#[derive(Clone, Copy)]
+pub enum SlotAlign {
+    Bottom,
+    HorizontalCenter,
+    Left,
+    Right,
+    Top,
+    VerticalCenter,
+}
+
+impl SlotAlign {
+    pub const ALL: [Self; 6] = [
+        Self::Left,
+        Self::HorizontalCenter,
+        Self::Right,
+        Self::Top,
+        Self::VerticalCenter,
+        Self::Bottom,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Bottom => "Align Bottom",
+            Self::HorizontalCenter => "Align Horizontal Center",
+            Self::Left => "Align Left",
+            Self::Right => "Align Right",
+            Self::Top => "Align Top",
+            Self::VerticalCenter => "Align Vertical Center",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum SlotDistribute {
+    Horizontal,
+    Vertical,
+}
+
+impl SlotDistribute {
+    pub const ALL: [Self; 2] = [Self::Horizontal, Self::Vertical];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Horizontal => "Distribute Horizontally",
+            Self::Vertical => "Distribute Vertically",
+        }
+    }
+}
+
+impl EditorApp {
+    pub(super) fn align_slots(&mut self, align: SlotAlign) -> bool {
+        let slots = self.movable_selected_slots();
+
+        if slots.is_empty() {
+            return false;
+        }
+
+        let Some(basis) = self.align_basis_rect() else {
+            return false;
+        };
+
+        let mut moved = false;
+
+        for (slot_id, rect) in slots {
+            let offset = match align {
+                SlotAlign::Bottom => vec2(0.0, basis.max.y - rect.max.y),
+                SlotAlign::HorizontalCenter => vec2(basis.center().x - rect.center().x, 0.0),
+                SlotAlign::Left => vec2(basis.min.x - rect.min.x, 0.0),
+                SlotAlign::Right => vec2(basis.max.x - rect.max.x, 0.0),
+                SlotAlign::Top => vec2(0.0, basis.min.y - rect.min.y),
+                SlotAlign::VerticalCenter => vec2(0.0, basis.center().y - rect.center().y),
+            };
+
+            moved |= self.move_slot_by(slot_id, offset);
+        }
+
+        moved
+    }
+
+    pub(super) fn distribute_slots(&mut self, distribute: SlotDistribute) -> bool {
+        let mut slots = self.movable_selected_slots();
+
+        if slots.len() < 3 {
+            return false;
+        }
+
+        // (start, end, size) of a rect along the distributing axis
+        let span = |rect: &Rect| match distribute {
+            SlotDistribute::Horizontal => (rect.min.x, rect.max.x, rect.width()),
+            SlotDistribute::Vertical => (rect.min.y, rect.max.y, rect.height()),
+        };
+
+        slots.sort_by(|(_, a), (_, b)| span(a).0.total_cmp(&span(b).0));
+
+        let start = span(&slots[0].1).0;
+        let end = slots
+            .iter()
+            .map(|(_, rect)| span(rect).1)
+            .fold(start, f32::max);
+        let total_size: f32 = slots.iter().map(|(_, rect)| span(rect).2).sum();
+
+        let gap = (end - start - total_size) / (slots.len() - 1) as f32;
+
+        let mut moved = false;
+        let mut cursor = start;
+
+        for (slot_id, rect) in slots {
+            let (rect_start, _, rect_size) = span(&rect);
+
+            let delta = (cursor - rect_start).round();
+
+            let offset = match distribute {
+                SlotDistribute::Horizontal => vec2(delta, 0.0),
+                SlotDistribute::Vertical => vec2(0.0, delta),
+            };
+
+            moved |= self.move_slot_by(slot_id, offset);
+
+            cursor += rect_size + gap;
+        }
+
+        moved
+    }
+
+    fn align_basis_rect(&self) -> Option<Rect> {
+        let doll = self.actived_doll.and_then(|id| self.ppd.get_doll(id))?;
+
+        let selected_slots = self.selected_slot_ids();
+
+        let basis_rect = doll
+            .slots
+            .iter()
+            .filter(|id| self.align_basis_slots.contains(id) && !selected_slots.contains(id))
+            .filter_map(|id| self.slot_bounds(*id))
+            .reduce(|a, b| a.union(b));
+
+        // fall back to the doll bounds when no basis slot is available
+        basis_rect.or_else(|| {
+            Some(Rect::from_min_size(
+                pos2(0.0, 0.0),
+                vec2(doll.width as f32, doll.height as f32),
+            ))
+        })
+    }
+
+    fn movable_selected_slots(&self) -> Vec<(u32, Rect)> {
+        self.selected_slot_ids()
+            .into_iter()
+            .filter(|id| !self.locked_slots.contains(id))
+            .filter_map(|id| self.slot_bounds(id).map(|rect| (id, rect)))
+            .collect()
+    }
+
+    fn move_slot_by(&mut self, slot_id: u32, offset: Vec2) -> bool {
+        let offset = offset.round();
+
+        if offset == Vec2::ZERO {
+            return false;
+        }
+
+        let Some(slot) = self.ppd.get_slot_mut(slot_id) else {
+            return false;
+        };
+
+        for position in slot.positions.iter_mut() {
+            position.x += offset.x;
+            position.y += offset.y;
+        }
+
+        true
+    }
+
+    fn slot_bounds(&self, slot_id: u32) -> Option<Rect> {
+        let slot = self.ppd.get_slot(slot_id)?;
+
+        slot.positions
+            .iter()
+            .map(|position| {
+                Rect::from_min_size(
+                    pos2(position.x, position.y),
+                    vec2(slot.width as f32, slot.height as f32),
+                )
+            })
+            .reduce(|a, b| a.union(b))
+    }
+}
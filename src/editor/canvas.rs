@@ -1,17 +1,24 @@
+mod grid;
 mod snap;
 
 use eframe::{
     egui::{
-        scroll_area::ScrollBarVisibility, CursorIcon, Id, PointerButton, ScrollArea, Sense, Ui,
+        scroll_area::ScrollBarVisibility, CursorIcon, Id, Painter, PointerButton, ScrollArea,
+        Sense, Ui,
     },
     epaint::{pos2, vec2, Color32, Pos2, Rect, Stroke, Vec2},
 };
 
 use crate::common::{determine_doll_rect, drag_move};
 
-use self::snap::{drag_snap, SnapInput, SnapOutput, SnapType};
+use self::{
+    grid::{grid_color, paint_grid, paint_rulers, ruler_rects, PIXEL_GRID_MIN_SCALE},
+    snap::{drag_snap, SnapGrid, SnapGuides, SnapInput, SnapOutput, SnapType},
+};
+
+use super::{actions::Action, guides::GuideOrientation, menu::menu_slot_arrange, EditorApp};
 
-use super::{actions::Action, EditorApp};
+const GUIDE_COLOR: Color32 = Color32::from_rgb(0, 180, 220);
 
 enum AuxiliaryLine {
     Horizontal(f32),
@@ -75,9 +82,13 @@ impl EditorApp {
 
                 self.viewport.rect = viewport_rect;
 
+                let is_shift_pressed = ui.input(|i| i.modifiers.shift);
+
                 if viewport_resp.drag_started_by(PointerButton::Primary) {
-                    if !self.window_slot_visible {
+                    if !self.window_slot_visible && !is_shift_pressed {
                         self.actived_slot = None;
+
+                        self.selected_slots.clear();
                     }
                 }
 
@@ -93,6 +104,17 @@ impl EditorApp {
                 let doll_rect =
                     determine_doll_rect(doll, &viewport_rect, scale, self.viewport.offset);
 
+                let doll_id = doll.id();
+
+                if viewport_resp.drag_started_by(PointerButton::Primary) {
+                    if !self.window_slot_visible {
+                        self.canvas_selection_origin = ui
+                            .ctx()
+                            .pointer_interact_pos()
+                            .map(|pos| ((pos - doll_rect.min) / scale).to_pos2());
+                    }
+                }
+
                 if ui.ui_contains_pointer() {
                     if let Some(pointer) = ui.ctx().pointer_interact_pos() {
                         self.actions.push_back(Action::CursorMoved(Some(
@@ -159,6 +181,11 @@ impl EditorApp {
 
                 let mut anchor_point = None;
                 let mut slot_drag_point = None;
+                let mut group_drag_offset: Option<Vec2> = None;
+
+                // in a group drag every position of every selected slot moves together
+                let is_group_drag = self.selected_slot_ids().len() > 1;
+                let mut selection_toggled_slot = None;
                 let mut auxiliary_lines = vec![];
 
                 for slot_id in slots {
@@ -169,6 +196,7 @@ impl EditorApp {
                     let is_actived_slot = self
                         .actived_slot
                         .map_or(false, |actived_slot| actived_slot == slot_id);
+                    let is_selected = self.selected_slots.contains(&slot_id);
                     let is_visible = self.visible_slots.contains(&slot_id);
                     let is_locked = self.locked_slots.contains(&slot_id);
 
@@ -186,30 +214,45 @@ impl EditorApp {
 
                         let slot_rect = Rect::from([min, max]);
 
-                        let slot_resp =
-                            ui.allocate_rect(slot_rect, Sense::drag())
-                                .context_menu(|ui| {
-                                    if is_actived_slot {
-                                        if ui.button("Edit slot").clicked() {
-                                            self.actions.push_back(Action::SlotEdit(slot_id));
+                        let slot_resp = ui
+                            .allocate_rect(slot_rect, Sense::click_and_drag())
+                            .context_menu(|ui| {
+                                if is_actived_slot {
+                                    if ui.button("Edit slot").clicked() {
+                                        self.actions.push_back(Action::SlotEdit(slot_id));
 
-                                            ui.close_menu();
-                                        }
+                                        ui.close_menu();
+                                    }
 
-                                        if ui.button("Delete slot").clicked() {
-                                            self.actions
-                                                .push_back(Action::SlotRemoveRequest(slot_id));
+                                    if ui.button("Delete slot").clicked() {
+                                        self.actions.push_back(Action::SlotRemoveRequest(slot_id));
 
-                                            ui.close_menu();
-                                        }
-                                    } else {
                                         ui.close_menu();
                                     }
-                                });
+
+                                    ui.separator();
+
+                                    menu_slot_arrange(ui, &self.shortcut, &mut self.actions);
+                                } else {
+                                    ui.close_menu();
+                                }
+                            });
 
                         // paint fragment
                         if is_visible {
-                            if slot_resp.dragged_by(PointerButton::Primary) {
+                            if slot_resp.clicked_by(PointerButton::Primary) {
+                                if is_shift_pressed {
+                                    selection_toggled_slot = Some(slot_id);
+                                } else {
+                                    self.selected_slots.clear();
+
+                                    self.actived_slot = Some(slot_id);
+                                }
+                            } else if slot_resp.dragged_by(PointerButton::Primary) {
+                                if !is_selected {
+                                    self.selected_slots.clear();
+                                }
+
                                 self.actived_slot = Some(slot_id);
                             }
 
@@ -547,6 +590,17 @@ impl EditorApp {
 
                                 // store updates
                                 if dragged || control_point_dragged || anchor_point_dragged {
+                                    self.history_pending = Some(
+                                        if anchor_point_dragged {
+                                            "Move anchor"
+                                        } else if control_point_dragged {
+                                            "Resize slot"
+                                        } else {
+                                            "Move slot"
+                                        }
+                                        .to_owned(),
+                                    );
+
                                     let mut drag_offset: Option<Vec2> = None;
                                     let mut drag_restrict = DragRestrict::default();
 
@@ -783,7 +837,17 @@ impl EditorApp {
                                                 }
                                             }
 
-                                            if is_shift_pressed
+                                            if dragged
+                                                && (top_left.x != position.x
+                                                    || top_left.y != position.y)
+                                            {
+                                                group_drag_offset = Some(vec2(
+                                                    top_left.x - position.x,
+                                                    top_left.y - position.y,
+                                                ));
+                                            }
+
+                                            if (is_shift_pressed || is_group_drag)
                                                 && (top_left.x != position.x
                                                     || top_left.y != position.y)
                                             {
@@ -807,7 +871,7 @@ impl EditorApp {
                                         }
                                     }
 
-                                    if dragged && is_shift_pressed {
+                                    if dragged && (is_shift_pressed || is_group_drag) {
                                         if let Some(offset) = drag_offset {
                                             for (index, position) in
                                                 new_positions.iter_mut().enumerate()
@@ -836,7 +900,13 @@ impl EditorApp {
                                 self.viewport.offset += drag_move(&slot_resp, scale, ui.ctx());
                             }
 
-                            if self.config.canvas_show_slot_boundaries {
+                            if is_selected {
+                                painter.rect_stroke(
+                                    slot_rect,
+                                    0.0,
+                                    Stroke::new(1.0, Color32::from_gray(180)),
+                                );
+                            } else if self.config.canvas_show_slot_boundaries {
                                 painter.rect_stroke(
                                     slot_rect,
                                     0.0,
@@ -880,6 +950,77 @@ impl EditorApp {
                     }
                 }
 
+                if let Some(slot_id) = selection_toggled_slot {
+                    self.toggle_slot_selection(slot_id);
+                }
+
+                // move other selected slots along with the actived one
+                if let Some(offset) = group_drag_offset {
+                    for slot_id in self.selected_slot_ids() {
+                        if Some(slot_id) == self.actived_slot
+                            || self.locked_slots.contains(&slot_id)
+                        {
+                            continue;
+                        }
+
+                        if let Some(slot) = self.ppd.get_slot_mut(slot_id) {
+                            for position in slot.positions.iter_mut() {
+                                position.x += offset.x;
+                                position.y += offset.y;
+                            }
+                        }
+                    }
+                }
+
+                // paint grids
+                if self.config.canvas_show_pixel_grid && scale >= PIXEL_GRID_MIN_SCALE {
+                    paint_grid(
+                        &painter,
+                        doll_rect,
+                        scale,
+                        Stroke::new(1.0, grid_color(is_dark, 24)),
+                    );
+                }
+
+                if self.config.canvas_show_layout_grid {
+                    paint_grid(
+                        &painter,
+                        doll_rect,
+                        self.config.canvas_layout_grid_size as f32 * scale,
+                        Stroke::new(1.0, grid_color(is_dark, 64)),
+                    );
+                }
+
+                self.ui_canvas_guides(ui, &painter, doll_id, doll_rect, viewport_rect);
+
+                // rubber-band selection
+                if let Some(origin) = self.canvas_selection_origin {
+                    if let Some(pointer) = ui.ctx().pointer_interact_pos() {
+                        let selection_rect =
+                            Rect::from_two_pos(doll_rect.min + origin.to_vec2() * scale, pointer);
+
+                        painter.rect(
+                            selection_rect,
+                            0.0,
+                            Color32::from_rgba_unmultiplied(120, 160, 255, 32),
+                            Stroke::new(1.0, Color32::from_rgb(120, 160, 255)),
+                        );
+
+                        if viewport_resp.drag_released() {
+                            let selection_rect = Rect::from_two_pos(
+                                origin,
+                                ((pointer - doll_rect.min) / scale).to_pos2(),
+                            );
+
+                            self.select_slots_in_rect(doll_id, selection_rect);
+                        }
+                    }
+
+                    if !viewport_resp.dragged_by(PointerButton::Primary) {
+                        self.canvas_selection_origin = None;
+                    }
+                }
+
                 if anchor_point.is_none() {
                     self.canvas_original_pos_anchor = None;
                 }
@@ -905,10 +1046,265 @@ impl EditorApp {
                     }
                 }
 
+                if self.config.canvas_show_rulers {
+                    paint_rulers(
+                        &painter,
+                        viewport_rect,
+                        doll_rect,
+                        scale,
+                        &ui.ctx().style().visuals,
+                    );
+                }
+
                 self.actions.push_back(Action::CanvasStateChanged(state));
             });
     }
 
+    pub(super) fn selected_slot_ids(&self) -> Vec<u32> {
+        let Some(actived_slot) = self.actived_slot else {
+            return vec![];
+        };
+
+        if !self.selected_slots.contains(&actived_slot) {
+            return vec![actived_slot];
+        }
+
+        let slots = self.selected_slot_ids_in_doll();
+
+        if slots.is_empty() {
+            vec![actived_slot]
+        } else {
+            slots
+        }
+    }
+
+    pub(super) fn toggle_slot_selection(&mut self, slot_id: u32) {
+        // the actived slot is always a part of the selection
+        if let Some(actived_slot) = self.actived_slot {
+            self.selected_slots.insert(actived_slot);
+        }
+
+        if self.selected_slots.remove(&slot_id) {
+            if self.actived_slot == Some(slot_id) {
+                self.actived_slot = self.selected_slot_ids_in_doll().first().copied();
+            }
+        } else {
+            self.selected_slots.insert(slot_id);
+
+            self.actived_slot = Some(slot_id);
+        }
+    }
+
+    fn ui_canvas_guides(
+        &mut self,
+        ui: &mut Ui,
+        painter: &Painter,
+        doll_id: u32,
+        doll_rect: Rect,
+        viewport_rect: Rect,
+    ) {
+        let scale = self.viewport.scale;
+
+        let pointer = ui.ctx().pointer_interact_pos();
+
+        let to_doll_value = |pos: Pos2, orientation: GuideOrientation| {
+            let point = ((pos - doll_rect.min) / scale).round();
+
+            match orientation {
+                GuideOrientation::Horizontal => point.y,
+                GuideOrientation::Vertical => point.x,
+            }
+        };
+
+        let to_screen_value = |value: f32, orientation: GuideOrientation| match orientation {
+            GuideOrientation::Horizontal => doll_rect.min.y + value * scale,
+            GuideOrientation::Vertical => doll_rect.min.x + value * scale,
+        };
+
+        let cursor_icon = |orientation: GuideOrientation| match orientation {
+            GuideOrientation::Horizontal => CursorIcon::ResizeVertical,
+            GuideOrientation::Vertical => CursorIcon::ResizeHorizontal,
+        };
+
+        // drag new guides out of the rulers
+        if self.config.canvas_show_rulers {
+            let (top_rect, left_rect) = ruler_rects(viewport_rect);
+
+            for (rect, orientation) in [
+                (top_rect, GuideOrientation::Horizontal),
+                (left_rect, GuideOrientation::Vertical),
+            ] {
+                let resp = ui.interact(rect, Id::new(("canvas_ruler", orientation)), Sense::drag());
+
+                if resp.hovered() {
+                    ui.ctx().set_cursor_icon(cursor_icon(orientation));
+                }
+
+                if resp.drag_started_by(PointerButton::Primary) {
+                    if let Some(pos) = pointer {
+                        let guides = self.guides.doll_mut(doll_id).get_mut(orientation);
+
+                        guides.push(to_doll_value(pos, orientation));
+
+                        self.canvas_dragging_guide = Some((orientation, guides.len() - 1));
+                    }
+                }
+            }
+        }
+
+        let mut lines = vec![];
+
+        if let Some(guides) = self.guides.doll(doll_id) {
+            for (index, value) in guides.horizontal.iter().enumerate() {
+                lines.push((GuideOrientation::Horizontal, index, *value));
+            }
+
+            for (index, value) in guides.vertical.iter().enumerate() {
+                lines.push((GuideOrientation::Vertical, index, *value));
+            }
+        }
+
+        for (orientation, index, value) in lines {
+            let value = to_screen_value(value, orientation);
+
+            let rect = match orientation {
+                GuideOrientation::Horizontal => Rect::from_min_max(
+                    pos2(viewport_rect.min.x, value - 2.0),
+                    pos2(viewport_rect.max.x, value + 2.0),
+                ),
+                GuideOrientation::Vertical => Rect::from_min_max(
+                    pos2(value - 2.0, viewport_rect.min.y),
+                    pos2(value + 2.0, viewport_rect.max.y),
+                ),
+            };
+
+            let resp = ui.interact(
+                rect,
+                Id::new(("canvas_guide", orientation, index)),
+                Sense::drag(),
+            );
+
+            if resp.hovered() {
+                ui.ctx().set_cursor_icon(cursor_icon(orientation));
+            }
+
+            if resp.drag_started_by(PointerButton::Primary) {
+                self.canvas_dragging_guide = Some((orientation, index));
+            }
+        }
+
+        // move or remove the dragged guide
+        if let Some((orientation, index)) = self.canvas_dragging_guide {
+            ui.ctx().set_cursor_icon(cursor_icon(orientation));
+
+            let is_dragging = ui.input(|i| i.pointer.primary_down());
+
+            let is_dropped_out = pointer.map_or(true, |pos| {
+                let (top_rect, left_rect) = ruler_rects(viewport_rect);
+
+                !viewport_rect.contains(pos)
+                    || (self.config.canvas_show_rulers
+                        && (top_rect.contains(pos) || left_rect.contains(pos)))
+            });
+
+            let guides = self.guides.doll_mut(doll_id).get_mut(orientation);
+
+            if is_dragging {
+                if let (Some(pos), Some(value)) = (pointer, guides.get_mut(index)) {
+                    *value = to_doll_value(pos, orientation);
+                }
+            } else {
+                // dropping a guide onto the rulers or out of the canvas removes it
+                if is_dropped_out && index < guides.len() {
+                    guides.remove(index);
+                }
+
+                self.canvas_dragging_guide = None;
+
+                self.actions.push_back(Action::GuidesChanged);
+            }
+        }
+
+        // paint guides
+        if let Some(guides) = self.guides.doll(doll_id) {
+            for (index, value) in guides.horizontal.iter().enumerate() {
+                let is_dragging =
+                    self.canvas_dragging_guide == Some((GuideOrientation::Horizontal, index));
+
+                painter.hline(
+                    painter.clip_rect().x_range(),
+                    to_screen_value(*value, GuideOrientation::Horizontal),
+                    Stroke::new(if is_dragging { 2.0 } else { 1.0 }, GUIDE_COLOR),
+                );
+            }
+
+            for (index, value) in guides.vertical.iter().enumerate() {
+                let is_dragging =
+                    self.canvas_dragging_guide == Some((GuideOrientation::Vertical, index));
+
+                painter.vline(
+                    to_screen_value(*value, GuideOrientation::Vertical),
+                    painter.clip_rect().y_range(),
+                    Stroke::new(if is_dragging { 2.0 } else { 1.0 }, GUIDE_COLOR),
+                );
+            }
+        }
+    }
+
+    fn select_slots_in_rect(&mut self, doll_id: u32, rect: Rect) {
+        let Some(doll) = self.ppd.get_doll(doll_id) else {
+            return;
+        };
+
+        let mut slots = vec![];
+
+        for slot_id in &doll.slots {
+            if !self.visible_slots.contains(slot_id) || self.locked_slots.contains(slot_id) {
+                continue;
+            }
+
+            let Some(slot) = self.ppd.get_slot(*slot_id) else {
+                continue;
+            };
+
+            let is_intersected = slot.positions.iter().any(|position| {
+                let min = pos2(position.x, position.y);
+                let max = min + vec2(slot.width as f32, slot.height as f32);
+
+                rect.intersects(Rect::from([min, max]))
+            });
+
+            if is_intersected {
+                slots.push(*slot_id);
+            }
+        }
+
+        if let Some(actived_slot) = self.actived_slot {
+            self.selected_slots.insert(actived_slot);
+        }
+
+        self.selected_slots.extend(slots.iter().copied());
+
+        if self
+            .actived_slot
+            .map_or(true, |id| !self.selected_slots.contains(&id))
+        {
+            self.actived_slot = slots.first().copied();
+        }
+    }
+
+    fn selected_slot_ids_in_doll(&self) -> Vec<u32> {
+        let Some(doll) = self.actived_doll.and_then(|id| self.ppd.get_doll(id)) else {
+            return vec![];
+        };
+
+        doll.slots
+            .iter()
+            .filter(|id| self.selected_slots.contains(id))
+            .copied()
+            .collect()
+    }
+
     fn snap_in_doll(
         &self,
         input: &SnapInput,
@@ -919,6 +1315,11 @@ impl EditorApp {
         let mut basis_rects = vec![doll_rect];
 
         for basis_slot_id in &self.align_basis_slots {
+            // selected slots move together with the dragged one
+            if *basis_slot_id != slot_id && self.selected_slots.contains(basis_slot_id) {
+                continue;
+            }
+
             if let Some(slot) = self.ppd.get_slot(*basis_slot_id) {
                 for (position_index, position) in slot.positions.iter().enumerate() {
                     if *basis_slot_id == slot_id && position_index == slot_position_index {
@@ -937,7 +1338,38 @@ impl EditorApp {
             }
         }
 
-        drag_snap(input, basis_rects, self.config.canvas_snap_tolerance)
+        let mut guides = SnapGuides::default();
+
+        if let Some(doll_guides) = self
+            .actived_doll
+            .and_then(|doll_id| self.guides.doll(doll_id))
+        {
+            let scale = self.viewport.scale;
+
+            guides.horizontal = doll_guides
+                .horizontal
+                .iter()
+                .map(|y| doll_rect.min.y + y * scale)
+                .collect();
+            guides.vertical = doll_guides
+                .vertical
+                .iter()
+                .map(|x| doll_rect.min.x + x * scale)
+                .collect();
+        }
+
+        let grid = self.config.canvas_snap_to_grid.then(|| SnapGrid {
+            origin: doll_rect.min,
+            spacing: self.config.canvas_layout_grid_size as f32 * self.viewport.scale,
+        });
+
+        drag_snap(
+            input,
+            basis_rects,
+            grid,
+            &guides,
+            self.config.canvas_snap_tolerance,
+        )
     }
 }
 
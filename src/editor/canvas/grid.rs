@@ -0,0 +1,139 @@
+use eframe::{
+    egui::{Painter, Visuals},
+    emath::Align2,
+    epaint::{pos2, Color32, FontId, Rect, Stroke},
+};
+
+pub(super) const PIXEL_GRID_MIN_SCALE: f32 = 8.0;
+
+const RULER_SIZE: f32 = 18.0;
+const RULER_TICK_MIN_SPACING: f32 = 60.0;
+const RULER_TICK_STEPS: [u32; 10] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000];
+
+pub(super) fn paint_grid(painter: &Painter, doll_rect: Rect, spacing: f32, stroke: Stroke) {
+    if spacing <= 0.0 {
+        return;
+    }
+
+    let clip_rect = painter.clip_rect().intersect(doll_rect);
+
+    if !clip_rect.is_positive() {
+        return;
+    }
+
+    // only lines within the visible part of the doll are painted
+    let first_x = ((clip_rect.min.x - doll_rect.min.x) / spacing).ceil();
+    let first_y = ((clip_rect.min.y - doll_rect.min.y) / spacing).ceil();
+
+    let mut x = doll_rect.min.x + first_x * spacing;
+
+    while x <= clip_rect.max.x {
+        painter.vline(x, clip_rect.y_range(), stroke);
+
+        x += spacing;
+    }
+
+    let mut y = doll_rect.min.y + first_y * spacing;
+
+    while y <= clip_rect.max.y {
+        painter.hline(clip_rect.x_range(), y, stroke);
+
+        y += spacing;
+    }
+}
+
+pub(super) fn paint_rulers(
+    painter: &Painter,
+    viewport_rect: Rect,
+    doll_rect: Rect,
+    scale: f32,
+    visuals: &Visuals,
+) {
+    let step = RULER_TICK_STEPS
+        .into_iter()
+        .find(|step| *step as f32 * scale >= RULER_TICK_MIN_SPACING)
+        .unwrap_or(*RULER_TICK_STEPS.last().unwrap()) as f32;
+
+    let background = visuals.extreme_bg_color;
+    let stroke = Stroke::new(1.0, visuals.weak_text_color());
+    let font_id = FontId::monospace(9.0);
+
+    let (top_rect, left_rect) = ruler_rects(viewport_rect);
+
+    painter.rect_filled(top_rect, 0.0, background);
+    painter.rect_filled(left_rect, 0.0, background);
+
+    // horizontal ruler
+    let first = ((top_rect.min.x - doll_rect.min.x) / scale / step).floor() as i32;
+    let last = ((top_rect.max.x - doll_rect.min.x) / scale / step).ceil() as i32;
+
+    for index in first..=last {
+        let value = index as f32 * step;
+        let x = doll_rect.min.x + value * scale;
+
+        if x < left_rect.max.x || x > top_rect.max.x {
+            continue;
+        }
+
+        painter.vline(x, top_rect.y_range(), stroke);
+
+        painter.text(
+            pos2(x + 2.0, top_rect.min.y),
+            Align2::LEFT_TOP,
+            value.to_string(),
+            font_id.clone(),
+            stroke.color,
+        );
+    }
+
+    // vertical ruler
+    let first = ((left_rect.min.y - doll_rect.min.y) / scale / step).floor() as i32;
+    let last = ((left_rect.max.y - doll_rect.min.y) / scale / step).ceil() as i32;
+
+    for index in first..=last {
+        let value = index as f32 * step;
+        let y = doll_rect.min.y + value * scale;
+
+        if y < top_rect.max.y || y > left_rect.max.y {
+            continue;
+        }
+
+        painter.hline(left_rect.x_range(), y, stroke);
+
+        painter.text(
+            pos2(left_rect.min.x + 1.0, y + 1.0),
+            Align2::LEFT_TOP,
+            value.to_string(),
+            font_id.clone(),
+            stroke.color,
+        );
+    }
+
+    let corner_rect = Rect::from_min_max(viewport_rect.min, pos2(left_rect.max.x, top_rect.max.y));
+
+    painter.rect_filled(corner_rect, 0.0, background);
+
+    painter.hline(top_rect.x_range(), top_rect.max.y, stroke);
+    painter.vline(left_rect.max.x, left_rect.y_range(), stroke);
+}
+
+pub(super) fn ruler_rects(viewport_rect: Rect) -> (Rect, Rect) {
+    let top_rect = Rect::from_min_max(
+        viewport_rect.min,
+        pos2(viewport_rect.max.x, viewport_rect.min.y + RULER_SIZE),
+    );
+    let left_rect = Rect::from_min_max(
+        viewport_rect.min,
+        pos2(viewport_rect.min.x + RULER_SIZE, viewport_rect.max.y),
+    );
+
+    (top_rect, left_rect)
+}
+
+pub(super) fn grid_color(is_dark: bool, alpha: u8) -> Color32 {
+    if is_dark {
+        Color32::from_rgba_unmultiplied(255, 255, 255, alpha)
+    } else {
+        Color32::from_rgba_unmultiplied(0, 0, 0, alpha)
+    }
+}
@@ -22,6 +22,17 @@ pub(super) struct SnapPointResult {
     pub y: Option<f32>,
 }
 
+#[derive(Default)]
+pub(super) struct SnapGuides {
+    pub horizontal: Vec<f32>,
+    pub vertical: Vec<f32>,
+}
+
+pub(super) struct SnapGrid {
+    pub origin: Pos2,
+    pub spacing: f32,
+}
+
 #[derive(Clone, Copy, Default)]
 pub(super) enum SnapType {
     #[default]
@@ -31,9 +42,20 @@ pub(super) enum SnapType {
     Both,
 }
 
-pub(super) fn drag_snap(input: &SnapInput, basis_rects: Vec<Rect>, tolerance: f32) -> SnapOutput {
+pub(super) fn drag_snap(
+    input: &SnapInput,
+    basis_rects: Vec<Rect>,
+    grid: Option<SnapGrid>,
+    guides: &SnapGuides,
+    tolerance: f32,
+) -> SnapOutput {
     let mut output = SnapOutput::default();
 
+    // guides take precedence over slots, and slots over the grid
+    if let Some(grid) = grid {
+        snap_to_grid(&grid, &input, &mut output, tolerance);
+    }
+
     for rect in basis_rects {
         snap_to_point(rect.center(), &input, &mut output, tolerance);
 
@@ -54,6 +76,8 @@ pub(super) fn drag_snap(input: &SnapInput, basis_rects: Vec<Rect>, tolerance: f3
         snap_to_point(rect.left_center(), &input, &mut output, tolerance);
     }
 
+    snap_to_guides(guides, &input, &mut output, tolerance);
+
     output
 }
 
@@ -89,50 +113,119 @@ fn snap_to_point(snap_point: Pos2, input: &SnapInput, output: &mut SnapOutput, t
         tolerance,
         &mut output.anchor,
     );
+}
+
+fn snap_to_grid(grid: &SnapGrid, input: &SnapInput, output: &mut SnapOutput, tolerance: f32) {
+    if grid.spacing <= 0.0 {
+        return;
+    }
+
+    let nearest =
+        |point: Pos2| grid.origin + ((point - grid.origin) / grid.spacing).round() * grid.spacing;
+
+    apply_snap(
+        nearest(input.min.0),
+        input.min.0,
+        input.min.1,
+        tolerance,
+        &mut output.min,
+    );
+
+    apply_snap(
+        nearest(input.max.0),
+        input.max.0,
+        input.max.1,
+        tolerance,
+        &mut output.max,
+    );
+
+    apply_snap(
+        nearest(input.center.0),
+        input.center.0,
+        input.center.1,
+        tolerance,
+        &mut output.center,
+    );
+
+    apply_snap(
+        nearest(input.anchor.0),
+        input.anchor.0,
+        input.anchor.1,
+        tolerance,
+        &mut output.anchor,
+    );
+}
+
+fn snap_to_guides(guides: &SnapGuides, input: &SnapInput, output: &mut SnapOutput, tolerance: f32) {
+    for ((point, snap_type), result) in [
+        (input.min, &mut output.min),
+        (input.max, &mut output.max),
+        (input.center, &mut output.center),
+        (input.anchor, &mut output.anchor),
+    ] {
+        let (snap_x, snap_y) = match snap_type {
+            SnapType::DisplayOnly => (false, false),
+            SnapType::X => (true, false),
+            SnapType::Y => (false, true),
+            SnapType::Both => (true, true),
+        };
+
+        for x in &guides.vertical {
+            if point.x == *x || (snap_x && (point.x - x).abs() <= tolerance) {
+                result.x = Some(*x);
+            }
+        }
+
+        for y in &guides.horizontal {
+            if point.y == *y || (snap_y && (point.y - y).abs() <= tolerance) {
+                result.y = Some(*y);
+            }
+        }
+    }
+}
 
-    fn apply_snap(
-        snap_point: Pos2,
-        point: Pos2,
-        snap_type: SnapType,
-        tolerance: f32,
-        result: &mut SnapPointResult,
-    ) {
-        match snap_type {
-            SnapType::DisplayOnly => {
-                if point.x == snap_point.x {
-                    result.x = Some(point.x);
-                }
-
-                if point.y == snap_point.y {
-                    result.y = Some(point.y);
-                }
+fn apply_snap(
+    snap_point: Pos2,
+    point: Pos2,
+    snap_type: SnapType,
+    tolerance: f32,
+    result: &mut SnapPointResult,
+) {
+    match snap_type {
+        SnapType::DisplayOnly => {
+            if point.x == snap_point.x {
+                result.x = Some(point.x);
             }
-            SnapType::X => {
-                if (point.x - snap_point.x).abs() <= tolerance {
-                    result.x = Some(snap_point.x);
-                }
-
-                if point.y == snap_point.y {
-                    result.y = Some(point.y);
-                }
+
+            if point.y == snap_point.y {
+                result.y = Some(point.y);
+            }
+        }
+        SnapType::X => {
+            if (point.x - snap_point.x).abs() <= tolerance {
+                result.x = Some(snap_point.x);
             }
-            SnapType::Y => {
-                if point.x == snap_point.x {
-                    result.x = Some(point.x);
-                }
-
-                if (point.y - snap_point.y).abs() <= tolerance {
-                    result.y = Some(snap_point.y);
-                }
+
+            if point.y == snap_point.y {
+                result.y = Some(point.y);
             }
-            SnapType::Both => {
-                if (point.x - snap_point.x).abs() <= tolerance {
-                    result.x = Some(snap_point.x);
-                }
-
-                if (point.y - snap_point.y).abs() <= tolerance {
-                    result.y = Some(snap_point.y);
-                }
+        }
+        SnapType::Y => {
+            if point.x == snap_point.x {
+                result.x = Some(point.x);
+            }
+
+            if (point.y - snap_point.y).abs() <= tolerance {
+                result.y = Some(snap_point.y);
+            }
+        }
+        SnapType::Both => {
+            if (point.x - snap_point.x).abs() <= tolerance {
+                result.x = Some(snap_point.x);
+            }
+
+            if (point.y - snap_point.y).abs() <= tolerance {
+                result.y = Some(snap_point.y);
             }
         }
     }
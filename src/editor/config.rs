@@ -1,7 +1,12 @@
 use std::path::PathBuf;
 
 pub struct Config {
+    pub canvas_layout_grid_size: u32,
+    pub canvas_show_layout_grid: bool,
+    pub canvas_show_pixel_grid: bool,
+    pub canvas_show_rulers: bool,
     pub canvas_show_slot_boundaries: bool,
+    pub canvas_snap_to_grid: bool,
     pub canvas_snap_tolerance: f32,
     pub file_path: Option<PathBuf>,
 }
@@ -9,7 +14,12 @@ pub struct Config {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            canvas_layout_grid_size: 8,
+            canvas_show_layout_grid: false,
+            canvas_show_pixel_grid: false,
+            canvas_show_rulers: false,
             canvas_show_slot_boundaries: false,
+            canvas_snap_to_grid: false,
             canvas_snap_tolerance: 10.0,
             file_path: None,
         }
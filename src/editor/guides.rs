@@ -0,0 +1,90 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+const GUIDES_EXTENSION: &'static str = "guides.json";
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq)]
+pub enum GuideOrientation {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct DollGuides {
+    pub horizontal: Vec<f32>,
+    pub vertical: Vec<f32>,
+}
+
+impl DollGuides {
+    pub fn get_mut(&mut self, orientation: GuideOrientation) -> &mut Vec<f32> {
+        match orientation {
+            GuideOrientation::Horizontal => &mut self.horizontal,
+            GuideOrientation::Vertical => &mut self.vertical,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.horizontal.is_empty() && self.vertical.is_empty()
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Guides {
+    pub dolls: HashMap<u32, DollGuides>,
+}
+
+impl Guides {
+    pub fn doll(&self, doll_id: u32) -> Option<&DollGuides> {
+        self.dolls.get(&doll_id)
+    }
+
+    pub fn doll_mut(&mut self, doll_id: u32) -> &mut DollGuides {
+        self.dolls.entry(doll_id).or_default()
+    }
+
+    pub fn load<P>(ppd_path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = guides_path(ppd_path);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save<P>(&self, ppd_path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = guides_path(ppd_path);
+
+        // dolls without guides are not worth a file
+        if self.dolls.values().all(|guides| guides.is_empty()) {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+
+            return Ok(());
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+}
+
+fn guides_path<P>(ppd_path: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    ppd_path.as_ref().with_extension(GUIDES_EXTENSION)
+}
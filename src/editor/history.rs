@@ -0,0 +1,366 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    mem,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use paperdoll_tar::paperdoll::{Manifest, PaperdollFactory};
+
+const HISTORY_CAPACITY: usize = 50;
+
+// images whose pixels differ from the state replaced by an undo or redo,
+// so that only their textures have to be uploaded again
+#[derive(Default)]
+pub struct ChangedImages {
+    pub dolls: HashSet<u32>,
+    pub fragments: HashSet<u32>,
+}
+
+pub struct HistoryEntry {
+    pub desc: String,
+    snapshot: Snapshot,
+}
+
+// pixels are kept out of the manifest and shared between snapshots,
+// so images unchanged by an edit are stored only once
+#[derive(Default)]
+struct Pixels {
+    dolls: HashMap<u32, Arc<Vec<u8>>>,
+    fragments: HashMap<u32, Arc<Vec<u8>>>,
+}
+
+struct Snapshot {
+    id: u64,
+    manifest: Manifest,
+    pixels: Pixels,
+}
+
+#[derive(Default)]
+pub struct History {
+    checkpoint: Option<Snapshot>,
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    next_id: u64,
+    saved_id: Option<u64>,
+}
+
+impl History {
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn commit(&mut self, ppd: &PaperdollFactory, desc: impl Into<String>) {
+        let id = self.next_id();
+        let snapshot = Snapshot::new(id, ppd, self.checkpoint.as_ref().map(|s| &s.pixels));
+
+        let Some(before) = self.checkpoint.replace(snapshot) else {
+            return;
+        };
+
+        self.undo_stack.push_back(HistoryEntry {
+            desc: desc.into(),
+            snapshot: before,
+        });
+
+        if self.undo_stack.len() > HISTORY_CAPACITY {
+            self.undo_stack.pop_front();
+        }
+
+        self.redo_stack.clear();
+    }
+
+    // whether the current state is the one last marked as saved
+    pub fn is_saved(&self) -> bool {
+        self.saved_id.is_some() && self.checkpoint.as_ref().map(|s| s.id) == self.saved_id
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_id = self.checkpoint.as_ref().map(|s| s.id);
+    }
+
+    pub fn redo(&mut self, ppd: &mut PaperdollFactory) -> Result<Option<ChangedImages>> {
+        let Some(entry) = self.redo_stack.pop() else {
+            return Ok(None);
+        };
+
+        let (current, changed) = self.restore(ppd, entry.snapshot)?;
+
+        self.undo_stack.push_back(HistoryEntry {
+            desc: entry.desc,
+            snapshot: current,
+        });
+
+        Ok(Some(changed))
+    }
+
+    pub fn redo_entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.redo_stack.iter().rev()
+    }
+
+    pub fn reset(&mut self, ppd: &PaperdollFactory) {
+        self.checkpoint = Some(Snapshot::new(self.next_id(), ppd, None));
+
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+
+        self.saved_id = None;
+    }
+
+    pub fn undo(&mut self, ppd: &mut PaperdollFactory) -> Result<Option<ChangedImages>> {
+        let Some(entry) = self.undo_stack.pop_back() else {
+            return Ok(None);
+        };
+
+        let (current, changed) = self.restore(ppd, entry.snapshot)?;
+
+        self.redo_stack.push(HistoryEntry {
+            desc: entry.desc,
+            snapshot: current,
+        });
+
+        Ok(Some(changed))
+    }
+
+    pub fn undo_entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.undo_stack.iter()
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    // loads the snapshot into ppd and returns the snapshot of the state it replaced
+    fn restore(
+        &mut self,
+        ppd: &mut PaperdollFactory,
+        snapshot: Snapshot,
+    ) -> Result<(Snapshot, ChangedImages)> {
+        let current = match self.checkpoint.take() {
+            Some(current) => current,
+            None => Snapshot::new(self.next_id(), ppd, Some(&snapshot.pixels)),
+        };
+
+        let Snapshot {
+            id,
+            mut manifest,
+            pixels,
+        } = snapshot;
+
+        // unchanged images share their pixels between snapshots
+        let changed = ChangedImages {
+            dolls: changed_ids(&current.pixels.dolls, &pixels.dolls),
+            fragments: changed_ids(&current.pixels.fragments, &pixels.fragments),
+        };
+
+        for doll in manifest.dolls.iter_mut() {
+            if let Some(data) = pixels.dolls.get(&doll.id()) {
+                doll.image.pixels = data.as_ref().clone();
+            }
+        }
+
+        for fragment in manifest.fragments.iter_mut() {
+            if let Some(data) = pixels.fragments.get(&fragment.id()) {
+                fragment.image.pixels = data.as_ref().clone();
+            }
+        }
+
+        *ppd = PaperdollFactory::from_manifest(manifest)?;
+
+        // the restored state keeps its id so that returning to a saved state is detected
+        self.checkpoint = Some(Snapshot::new(id, ppd, Some(&pixels)));
+
+        Ok((current, changed))
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+
+        self.next_id
+    }
+}
+
+impl Snapshot {
+    fn new(id: u64, ppd: &PaperdollFactory, shared: Option<&Pixels>) -> Self {
+        let mut manifest = ppd.to_manifest();
+        let mut pixels = Pixels::default();
+
+        for doll in manifest.dolls.iter_mut() {
+            let data = mem::take(&mut doll.image.pixels);
+
+            pixels.dolls.insert(
+                doll.id(),
+                share_pixels(data, shared.and_then(|s| s.dolls.get(&doll.id()))),
+            );
+        }
+
+        for fragment in manifest.fragments.iter_mut() {
+            let data = mem::take(&mut fragment.image.pixels);
+
+            pixels.fragments.insert(
+                fragment.id(),
+                share_pixels(data, shared.and_then(|s| s.fragments.get(&fragment.id()))),
+            );
+        }
+
+        Self {
+            id,
+            manifest,
+            pixels,
+        }
+    }
+}
+
+fn changed_ids(
+    before: &HashMap<u32, Arc<Vec<u8>>>,
+    after: &HashMap<u32, Arc<Vec<u8>>>,
+) -> HashSet<u32> {
+    after
+        .iter()
+        .filter(|(id, data)| {
+            before
+                .get(*id)
+                .map_or(true, |shared| !Arc::ptr_eq(shared, *data))
+        })
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+fn share_pixels(data: Vec<u8>, shared: Option<&Arc<Vec<u8>>>) -> Arc<Vec<u8>> {
+    match shared {
+        Some(shared) if shared.as_slice() == data.as_slice() => Arc::clone(shared),
+        _ => Arc::new(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use paperdoll_tar::paperdoll::{ColorType, ImageData, PaperdollFactory};
+
+    use super::{History, HISTORY_CAPACITY};
+
+    fn edit(ppd: &mut PaperdollFactory, history: &mut History, name: &str) {
+        ppd.meta.name = name.to_owned();
+
+        history.commit(ppd, name);
+    }
+
+    fn set_pixel(ppd: &mut PaperdollFactory, fragment_id: u32, value: u8) {
+        ppd.get_fragment_mut(fragment_id).unwrap().image = ImageData {
+            width: 1,
+            height: 1,
+            color_type: ColorType::Rgba,
+            pixels: vec![value; 4],
+        };
+    }
+
+    #[test]
+    fn capacity_drops_oldest_entries() {
+        let mut ppd = PaperdollFactory::default();
+        let mut history = History::default();
+
+        history.reset(&ppd);
+
+        for i in 0..HISTORY_CAPACITY + 10 {
+            edit(&mut ppd, &mut history, &i.to_string());
+        }
+
+        assert_eq!(history.undo_len(), HISTORY_CAPACITY);
+        assert_eq!(history.undo_entries().next().unwrap().desc, "10");
+
+        while history.undo(&mut ppd).unwrap().is_some() {}
+
+        assert_eq!(ppd.meta.name, "9");
+    }
+
+    #[test]
+    fn commit_truncates_redo() {
+        let mut ppd = PaperdollFactory::default();
+        let mut history = History::default();
+
+        history.reset(&ppd);
+
+        edit(&mut ppd, &mut history, "a");
+        edit(&mut ppd, &mut history, "b");
+
+        assert!(history.undo(&mut ppd).unwrap().is_some());
+        assert_eq!(ppd.meta.name, "a");
+        assert!(history.can_redo());
+
+        edit(&mut ppd, &mut history, "c");
+
+        assert!(!history.can_redo());
+        assert!(history.redo(&mut ppd).unwrap().is_none());
+        assert_eq!(ppd.meta.name, "c");
+
+        assert!(history.undo(&mut ppd).unwrap().is_some());
+        assert_eq!(ppd.meta.name, "a");
+    }
+
+    #[test]
+    fn undo_back_to_saved_state() {
+        let mut ppd = PaperdollFactory::default();
+        let mut history = History::default();
+
+        history.reset(&ppd);
+        history.mark_saved();
+
+        assert!(history.is_saved());
+
+        edit(&mut ppd, &mut history, "a");
+
+        assert!(!history.is_saved());
+
+        history.undo(&mut ppd).unwrap();
+
+        assert!(history.is_saved());
+
+        history.redo(&mut ppd).unwrap();
+
+        assert!(!history.is_saved());
+    }
+
+    #[test]
+    fn undo_reports_changed_images() {
+        let mut ppd = PaperdollFactory::default();
+        let mut history = History::default();
+
+        let changed_id = ppd.add_fragment().unwrap();
+        let kept_id = ppd.add_fragment().unwrap();
+
+        set_pixel(&mut ppd, changed_id, 1);
+        set_pixel(&mut ppd, kept_id, 2);
+
+        history.reset(&ppd);
+
+        set_pixel(&mut ppd, changed_id, 3);
+
+        history.commit(&ppd, "image");
+
+        edit(&mut ppd, &mut history, "name");
+
+        let changed = history.undo(&mut ppd).unwrap().unwrap();
+
+        assert!(changed.dolls.is_empty());
+        assert!(changed.fragments.is_empty());
+
+        let changed = history.undo(&mut ppd).unwrap().unwrap();
+
+        assert!(changed.dolls.is_empty());
+        assert_eq!(changed.fragments, HashSet::from([changed_id]));
+        assert_eq!(
+            ppd.get_fragment(changed_id).unwrap().image.pixels,
+            vec![1; 4]
+        );
+
+        let changed = history.redo(&mut ppd).unwrap().unwrap();
+
+        assert_eq!(changed.fragments, HashSet::from([changed_id]));
+    }
+}
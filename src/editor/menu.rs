@@ -1,6 +1,17 @@
+use std::collections::VecDeque;
+
 use eframe::egui::{menu, Button, Ui};
 
-use super::{actions::Action, example::Example, EditorApp};
+use super::{
+    actions::Action,
+    align::{SlotAlign, SlotDistribute},
+    example::Example,
+    recovery::AUTOSAVE_INTERVALS,
+    shortcut::Shortcut,
+    EditorApp,
+};
+
+const LAYOUT_GRID_SIZES: [u32; 4] = [4, 8, 16, 32];
 
 impl EditorApp {
     pub(super) fn menu_doll(&mut self, ui: &mut Ui, id: Option<u32>) {
@@ -27,7 +38,7 @@ impl EditorApp {
 
             if ui
                 .add_enabled(
-                    !doll.unwrap().image.is_empty(),
+                    doll.map_or(false, |doll| !doll.image.is_empty()),
                     Button::new("Resize to Background Size"),
                 )
                 .clicked()
@@ -37,6 +48,19 @@ impl EditorApp {
 
                 ui.close_menu();
             }
+
+            if ui
+                .add_enabled(
+                    doll.map_or(false, |doll| !doll.path.is_empty()),
+                    Button::new("Reload Background from Disk"),
+                )
+                .clicked()
+            {
+                self.actions
+                    .push_back(Action::DollBackgroundReload(doll.unwrap().id()));
+
+                ui.close_menu();
+            }
         });
     }
 
@@ -64,6 +88,19 @@ impl EditorApp {
                 ui.close_menu();
             }
 
+            if ui
+                .add_enabled(
+                    fragment.map_or(false, |fragment| !fragment.path.is_empty()),
+                    Button::new("Reload from Disk"),
+                )
+                .clicked()
+            {
+                self.actions
+                    .push_back(Action::FragmentReload(fragment.unwrap().id()));
+
+                ui.close_menu();
+            }
+
             ui.separator();
 
             if ui.button("Manage Associated Slots").clicked() {
@@ -73,6 +110,15 @@ impl EditorApp {
                 ui.close_menu();
             }
         });
+
+        ui.separator();
+
+        if ui.button("Find Duplicate Fragments").clicked() {
+            self.actions
+                .push_back(Action::WindowDuplicatesVisible(true));
+
+            ui.close_menu();
+        }
     }
 
     pub(super) fn menu_slot(&mut self, ui: &mut Ui, id: Option<u32>) {
@@ -115,7 +161,7 @@ impl EditorApp {
 
         if ui
             .add_enabled(
-                self.actived_doll.is_some() && self.slot_copy.is_some(),
+                self.actived_doll.is_some() && !self.slot_copy.is_empty(),
                 Button::new("Paste Slot")
                     .shortcut_text(ui.ctx().format_shortcut(&self.shortcut.slot_paste)),
             )
@@ -143,6 +189,10 @@ impl EditorApp {
 
                 ui.close_menu();
             }
+
+            ui.separator();
+
+            menu_slot_arrange(ui, &self.shortcut, &mut self.actions);
         });
     }
 
@@ -265,6 +315,126 @@ impl EditorApp {
 
                 ui.separator();
 
+                if ui.button("Unpack to Folder").clicked() {
+                    self.actions.push_back(Action::FileUnpack);
+
+                    ui.close_menu();
+                }
+
+                if ui.button("Pack Folder").clicked() {
+                    self.actions.push_back(Action::FilePack);
+
+                    ui.close_menu();
+                }
+
+                ui.separator();
+
+                ui.menu_button("Import OpenRaster", |ui| {
+                    if ui.button("Layers as Fragments").clicked() {
+                        self.actions.push_back(Action::FragmentCreateFromOra(false));
+
+                        ui.close_menu();
+                    }
+
+                    if ui.button("Layer Groups as Slots").clicked() {
+                        self.actions.push_back(Action::FragmentCreateFromOra(true));
+
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("Export", |ui| {
+                    if ui.button("Atlas of Fragments").clicked() {
+                        self.actions.push_back(Action::ExportAtlas(false));
+
+                        ui.close_menu();
+                    }
+
+                    if ui
+                        .button("Atlas of Fragments and Doll Backgrounds")
+                        .clicked()
+                    {
+                        self.actions.push_back(Action::ExportAtlas(true));
+
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+
+                    if ui.button("Ren'Py Layered Images").clicked() {
+                        self.actions.push_back(Action::ExportRenpy);
+
+                        ui.close_menu();
+                    }
+
+                    if ui
+                        .add_enabled(
+                            self.actived_doll.is_some(),
+                            Button::new("Godot Scene of Current Doll"),
+                        )
+                        .clicked()
+                    {
+                        if let Some(doll_id) = self.actived_doll {
+                            self.actions.push_back(Action::ExportGodot(doll_id));
+                        }
+
+                        ui.close_menu();
+                    }
+                });
+
+                ui.separator();
+
+                if ui.button("Reload All Images from Disk").clicked() {
+                    self.actions.push_back(Action::ImagesReload);
+
+                    ui.close_menu();
+                }
+
+                if ui.button("Relink Missing Images").clicked() {
+                    self.actions.push_back(Action::WindowRelinkVisible(true));
+
+                    ui.close_menu();
+                }
+
+                let mut watch_images = self.storage.watch_images;
+
+                if ui
+                    .checkbox(&mut watch_images, "Watch Source Images")
+                    .clicked()
+                {
+                    self.actions.push_back(Action::WatchImages(watch_images));
+
+                    ui.close_menu();
+                }
+
+                ui.menu_button("Autosave", |ui| {
+                    if ui
+                        .radio(self.storage.autosave_interval == 0, "Off")
+                        .clicked()
+                    {
+                        self.actions.push_back(Action::AutosaveIntervalChanged(0));
+
+                        ui.close_menu();
+                    }
+
+                    for interval in AUTOSAVE_INTERVALS {
+                        if ui
+                            .radio(
+                                self.storage.autosave_interval == interval,
+                                format!("Every {} min", interval / 60),
+                            )
+                            .clicked()
+                        {
+                            self.actions
+                                .push_back(Action::AutosaveIntervalChanged(interval));
+
+                            ui.close_menu();
+                        }
+                    }
+                });
+
+                ui.separator();
+
                 if ui
                     .add(
                         Button::new("Quit")
@@ -278,6 +448,43 @@ impl EditorApp {
                 }
             });
 
+            ui.menu_button("Edit", |ui| {
+                if ui
+                    .add_enabled(
+                        self.history.can_undo(),
+                        Button::new("Undo")
+                            .shortcut_text(ui.ctx().format_shortcut(&self.shortcut.history_undo)),
+                    )
+                    .clicked()
+                {
+                    self.actions.push_back(Action::HistoryUndo);
+
+                    ui.close_menu();
+                }
+
+                if ui
+                    .add_enabled(
+                        self.history.can_redo(),
+                        Button::new("Redo")
+                            .shortcut_text(ui.ctx().format_shortcut(&self.shortcut.history_redo)),
+                    )
+                    .clicked()
+                {
+                    self.actions.push_back(Action::HistoryRedo);
+
+                    ui.close_menu();
+                }
+
+                ui.separator();
+
+                if ui.button("History").clicked() {
+                    self.actions
+                        .push_back(Action::WindowHistoryVisible(!self.window_history_visible));
+
+                    ui.close_menu();
+                }
+            });
+
             ui.menu_button("View", |ui| {
                 if ui
                     .add(
@@ -353,6 +560,68 @@ impl EditorApp {
 
                     ui.close_menu();
                 }
+
+                ui.separator();
+
+                if ui.button("Toggle Pixel Grid").clicked() {
+                    self.actions.push_back(Action::CanvasShowPixelGrid(
+                        !self.config.canvas_show_pixel_grid,
+                    ));
+
+                    ui.close_menu();
+                }
+
+                if ui.button("Toggle Layout Grid").clicked() {
+                    self.actions.push_back(Action::CanvasShowLayoutGrid(
+                        !self.config.canvas_show_layout_grid,
+                    ));
+
+                    ui.close_menu();
+                }
+
+                ui.menu_button("Layout Grid Size", |ui| {
+                    for size in LAYOUT_GRID_SIZES {
+                        if ui
+                            .radio(
+                                self.config.canvas_layout_grid_size == size,
+                                format!("{} px", size),
+                            )
+                            .clicked()
+                        {
+                            self.actions.push_back(Action::CanvasLayoutGridSize(size));
+
+                            ui.close_menu();
+                        }
+                    }
+                });
+
+                if ui.button("Toggle Snap to Grid").clicked() {
+                    self.actions
+                        .push_back(Action::CanvasSnapToGrid(!self.config.canvas_snap_to_grid));
+
+                    ui.close_menu();
+                }
+
+                if ui.button("Toggle Rulers").clicked() {
+                    self.actions
+                        .push_back(Action::CanvasShowRulers(!self.config.canvas_show_rulers));
+
+                    ui.close_menu();
+                }
+
+                if ui.button("Guides").clicked() {
+                    self.actions
+                        .push_back(Action::WindowGuidesVisible(!self.window_guides_visible));
+
+                    ui.close_menu();
+                }
+
+                if ui.button("Problems").clicked() {
+                    self.actions
+                        .push_back(Action::WindowProblemsVisible(!self.window_problems_visible));
+
+                    ui.close_menu();
+                }
             });
 
             ui.menu_button("Doll", |ui| {
@@ -377,3 +646,43 @@ impl EditorApp {
         });
     }
 }
+
+pub(super) fn menu_slot_arrange(ui: &mut Ui, shortcut: &Shortcut, actions: &mut VecDeque<Action>) {
+    ui.menu_button("Align", |ui| {
+        for align in SlotAlign::ALL {
+            if matches!(align, SlotAlign::Top) {
+                ui.separator();
+            }
+
+            if ui
+                .add(
+                    Button::new(align.description())
+                        .shortcut_text(ui.ctx().format_shortcut(shortcut.slot_align(align))),
+                )
+                .clicked()
+            {
+                actions.push_back(Action::SlotAlign(align));
+
+                ui.close_menu();
+            }
+        }
+    });
+
+    ui.menu_button("Distribute", |ui| {
+        for distribute in SlotDistribute::ALL {
+            if ui
+                .add(
+                    Button::new(distribute.description()).shortcut_text(
+                        ui.ctx()
+                            .format_shortcut(shortcut.slot_distribute(distribute)),
+                    ),
+                )
+                .clicked()
+            {
+                actions.push_back(Action::SlotDistribute(distribute));
+
+                ui.close_menu();
+            }
+        }
+    });
+}
@@ -0,0 +1,108 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use directories_next::ProjectDirs;
+use eframe::egui::Context;
+use paperdoll_tar::{paperdoll::PaperdollFactory, EXTENSION_NAME};
+
+use super::{EditorApp, APP_ID};
+
+pub const AUTOSAVE_INTERVALS: [u64; 4] = [60, 300, 600, 1800];
+
+const RECOVERY_DIR: &'static str = "recovery";
+const RECOVERY_UNTITLED: &'static str = "untitled";
+
+impl EditorApp {
+    pub(super) fn autosave(&mut self, ctx: &Context) {
+        let now = ctx.input(|i| i.time);
+
+        if self.storage.autosave_interval == 0 || !self.is_dirty {
+            self.autosave_time = now;
+
+            return;
+        }
+
+        let interval = self.storage.autosave_interval as f64;
+        let elapsed = now - self.autosave_time;
+
+        if elapsed < interval {
+            ctx.request_repaint_after(Duration::from_secs_f64(interval - elapsed));
+
+            return;
+        }
+
+        self.autosave_time = now;
+
+        match write_recovery(&self.ppd, self.config.file_path.as_deref()) {
+            Ok(path) => log::info!("Writing recovery file {:?} successfully.", path),
+            Err(err) => log::error!("Writing recovery file failed: {}", err),
+        }
+    }
+}
+
+pub fn find_recovery(file_path: Option<&Path>) -> Option<PathBuf> {
+    let recovery_path = recovery_path(file_path)?;
+
+    let recovery_modified = fs::metadata(&recovery_path)
+        .and_then(|meta| meta.modified())
+        .ok()?;
+
+    if let Some(file_path) = file_path {
+        if let Ok(modified) = fs::metadata(file_path).and_then(|meta| meta.modified()) {
+            if modified >= recovery_modified {
+                return None;
+            }
+        }
+    }
+
+    Some(recovery_path)
+}
+
+pub fn remove_recovery(file_path: Option<&Path>) {
+    let Some(path) = recovery_path(file_path) else {
+        return;
+    };
+
+    if !path.exists() {
+        return;
+    }
+
+    if let Err(err) = fs::remove_file(&path) {
+        log::warn!("Removing recovery file {:?} failed: {}", path, err);
+    }
+}
+
+fn recovery_path(file_path: Option<&Path>) -> Option<PathBuf> {
+    let dirs = ProjectDirs::from("", "", APP_ID)?;
+
+    // one recovery file per project, named after the full path of the project
+    let name: String = file_path.map_or(RECOVERY_UNTITLED.to_owned(), |path| {
+        path.to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    });
+
+    Some(
+        dirs.data_dir()
+            .join(RECOVERY_DIR)
+            .join(format!("{}.{}", name, EXTENSION_NAME)),
+    )
+}
+
+fn write_recovery(ppd: &PaperdollFactory, file_path: Option<&Path>) -> Result<PathBuf> {
+    let path = recovery_path(file_path)
+        .ok_or_else(|| anyhow!("Failed to locate the app data directory."))?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    paperdoll_tar::save(&mut ppd.to_manifest(), &path)?;
+
+    Ok(path)
+}
@@ -0,0 +1,134 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fs::{self, FileType},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use paperdoll_tar::paperdoll::PaperdollFactory;
+
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum RelinkTarget {
+    Doll(u32),
+    Fragment(u32),
+}
+
+pub(super) enum RelinkStatus {
+    Found,
+    Missing,
+    SizeChanged((u32, u32), (u32, u32)),
+}
+
+pub(super) struct RelinkEntry {
+    pub target: RelinkTarget,
+    pub name: String,
+    pub old_path: String,
+    pub new_path: Option<PathBuf>,
+    pub status: RelinkStatus,
+}
+
+// matches images whose source file no longer exists with files of the same name under `root`
+pub(super) fn find_relinks(ppd: &PaperdollFactory, root: &Path) -> Result<Vec<RelinkEntry>> {
+    let mut files = HashMap::new();
+
+    collect_files(root, &mut files)?;
+
+    let mut sources: Vec<(RelinkTarget, String, String, (u32, u32))> = vec![];
+
+    for (id, doll) in ppd.dolls() {
+        if !doll.image.is_empty() {
+            sources.push((
+                RelinkTarget::Doll(*id),
+                doll.desc.clone(),
+                doll.path.clone(),
+                (doll.image.width, doll.image.height),
+            ));
+        }
+    }
+
+    for (id, fragment) in ppd.fragments() {
+        sources.push((
+            RelinkTarget::Fragment(*id),
+            fragment.desc.clone(),
+            fragment.path.clone(),
+            (fragment.image.width, fragment.image.height),
+        ));
+    }
+
+    let mut entries = vec![];
+
+    for (target, name, old_path, size) in sources {
+        if old_path.is_empty() || Path::new(&old_path).exists() {
+            continue;
+        }
+
+        let new_path = Path::new(&old_path)
+            .file_name()
+            .and_then(|file_name| files.get(file_name))
+            .cloned();
+
+        let status = match &new_path {
+            Some(new_path) => match image::image_dimensions(new_path) {
+                Ok(new_size) if new_size != size => RelinkStatus::SizeChanged(size, new_size),
+                Ok(_) => RelinkStatus::Found,
+                Err(_) => RelinkStatus::Missing,
+            },
+            None => RelinkStatus::Missing,
+        };
+
+        entries.push(RelinkEntry {
+            target,
+            name,
+            old_path,
+            new_path: (!matches!(status, RelinkStatus::Missing))
+                .then_some(new_path)
+                .flatten(),
+            status,
+        });
+    }
+
+    entries.sort_by_key(|entry| match entry.target {
+        RelinkTarget::Doll(id) => (0, id),
+        RelinkTarget::Fragment(id) => (1, id),
+    });
+
+    Ok(entries)
+}
+
+fn collect_files(dir: &Path, files: &mut HashMap<OsString, PathBuf>) -> Result<()> {
+    // entries which can not be read are skipped
+    let mut entries: Vec<(PathBuf, FileType)> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+
+            Some((entry.path(), entry.file_type().ok()?))
+        })
+        .collect();
+
+    // sorted so that the same file wins every time
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (path, file_type) in entries {
+        // symlinked directories are not followed, they may lead back to a parent
+        let is_file = if file_type.is_symlink() {
+            path.is_file()
+        } else {
+            file_type.is_file()
+        };
+
+        if file_type.is_dir() {
+            if let Err(err) = collect_files(&path, files) {
+                log::warn!("Scanning {:?} failed: {}", path, err);
+            }
+        } else if is_file {
+            if let Some(file_name) = path.file_name() {
+                files
+                    .entry(file_name.to_os_string())
+                    .or_insert(path.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -3,7 +3,11 @@ use eframe::{
     epaint::vec2,
 };
 
-use super::{actions::Action, EditorApp};
+use super::{
+    actions::Action,
+    align::{SlotAlign, SlotDistribute},
+    EditorApp,
+};
 
 pub(super) struct Shortcut {
     pub app_quit: KeyboardShortcut,
@@ -11,7 +15,17 @@ pub(super) struct Shortcut {
     pub file_open: KeyboardShortcut,
     pub file_save: KeyboardShortcut,
     pub file_save_as: KeyboardShortcut,
+    pub history_redo: KeyboardShortcut,
+    pub history_undo: KeyboardShortcut,
+    pub slot_align_bottom: KeyboardShortcut,
+    pub slot_align_horizontal_center: KeyboardShortcut,
+    pub slot_align_left: KeyboardShortcut,
+    pub slot_align_right: KeyboardShortcut,
+    pub slot_align_top: KeyboardShortcut,
+    pub slot_align_vertical_center: KeyboardShortcut,
     pub slot_copy: KeyboardShortcut,
+    pub slot_distribute_horizontal: KeyboardShortcut,
+    pub slot_distribute_vertical: KeyboardShortcut,
     pub slot_duplicate: KeyboardShortcut,
     pub slot_paste: KeyboardShortcut,
     pub viewport_center: KeyboardShortcut,
@@ -33,7 +47,38 @@ impl Default for Shortcut {
             file_open: KeyboardShortcut::new(Modifiers::CTRL, Key::O),
             file_save: KeyboardShortcut::new(Modifiers::CTRL, Key::S),
             file_save_as: KeyboardShortcut::new(Modifiers::CTRL | Modifiers::SHIFT, Key::S),
+            history_redo: KeyboardShortcut::new(Modifiers::CTRL | Modifiers::SHIFT, Key::Z),
+            history_undo: KeyboardShortcut::new(Modifiers::CTRL, Key::Z),
+            slot_align_bottom: KeyboardShortcut::new(
+                Modifiers::CTRL | Modifiers::ALT,
+                Key::ArrowDown,
+            ),
+            slot_align_horizontal_center: KeyboardShortcut::new(
+                Modifiers::CTRL | Modifiers::ALT,
+                Key::H,
+            ),
+            slot_align_left: KeyboardShortcut::new(
+                Modifiers::CTRL | Modifiers::ALT,
+                Key::ArrowLeft,
+            ),
+            slot_align_right: KeyboardShortcut::new(
+                Modifiers::CTRL | Modifiers::ALT,
+                Key::ArrowRight,
+            ),
+            slot_align_top: KeyboardShortcut::new(Modifiers::CTRL | Modifiers::ALT, Key::ArrowUp),
+            slot_align_vertical_center: KeyboardShortcut::new(
+                Modifiers::CTRL | Modifiers::ALT,
+                Key::V,
+            ),
             slot_copy: KeyboardShortcut::new(Modifiers::CTRL, Key::C),
+            slot_distribute_horizontal: KeyboardShortcut::new(
+                Modifiers::CTRL | Modifiers::ALT | Modifiers::SHIFT,
+                Key::H,
+            ),
+            slot_distribute_vertical: KeyboardShortcut::new(
+                Modifiers::CTRL | Modifiers::ALT | Modifiers::SHIFT,
+                Key::V,
+            ),
             slot_duplicate: KeyboardShortcut::new(Modifiers::CTRL, Key::D),
             slot_paste: KeyboardShortcut::new(Modifiers::CTRL, Key::V),
             viewport_center: KeyboardShortcut::new(Modifiers::CTRL, Key::J),
@@ -49,6 +94,26 @@ impl Default for Shortcut {
     }
 }
 
+impl Shortcut {
+    pub fn slot_align(&self, align: SlotAlign) -> &KeyboardShortcut {
+        match align {
+            SlotAlign::Bottom => &self.slot_align_bottom,
+            SlotAlign::HorizontalCenter => &self.slot_align_horizontal_center,
+            SlotAlign::Left => &self.slot_align_left,
+            SlotAlign::Right => &self.slot_align_right,
+            SlotAlign::Top => &self.slot_align_top,
+            SlotAlign::VerticalCenter => &self.slot_align_vertical_center,
+        }
+    }
+
+    pub fn slot_distribute(&self, distribute: SlotDistribute) -> &KeyboardShortcut {
+        match distribute {
+            SlotDistribute::Horizontal => &self.slot_distribute_horizontal,
+            SlotDistribute::Vertical => &self.slot_distribute_vertical,
+        }
+    }
+}
+
 impl EditorApp {
     pub(super) fn handle_shortcut(&mut self, ctx: &Context) {
         if self.has_modal_open() || self.has_drag_value_focused {
@@ -72,13 +137,36 @@ impl EditorApp {
                 self.actions.push_back(Action::FileSaveAs);
             }
 
+            if i.consume_shortcut(&self.shortcut.history_redo) {
+                self.actions.push_back(Action::HistoryRedo);
+            }
+
+            if i.consume_shortcut(&self.shortcut.history_undo) {
+                self.actions.push_back(Action::HistoryUndo);
+            }
+
             if let Some(slot_id) = self.actived_slot {
                 if i.consume_shortcut(&self.shortcut.slot_copy) {
                     self.actions.push_back(Action::SlotCopy(slot_id));
                 }
             }
 
-            if self.actived_doll.is_some() && self.slot_copy.is_some() {
+            if self.actived_slot.is_some() {
+                // distribute shortcuts share keys with the align ones
+                for distribute in SlotDistribute::ALL {
+                    if i.consume_shortcut(self.shortcut.slot_distribute(distribute)) {
+                        self.actions.push_back(Action::SlotDistribute(distribute));
+                    }
+                }
+
+                for align in SlotAlign::ALL {
+                    if i.consume_shortcut(self.shortcut.slot_align(align)) {
+                        self.actions.push_back(Action::SlotAlign(align));
+                    }
+                }
+            }
+
+            if self.actived_doll.is_some() && !self.slot_copy.is_empty() {
                 if i.consume_shortcut(&self.shortcut.slot_paste) {
                     self.actions
                         .push_back(Action::SlotPaste(self.actived_doll.unwrap()));
@@ -2,38 +2,56 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 
-use crate::fixed_vec::FixedVec;
+use crate::{common::restore_storage_value, fixed_vec::FixedVec};
 
+const AUTOSAVE_INTERVAL: u64 = 300;
 const RECENT_FILE_COUNT: usize = 5;
 
+const KEY_AUTOSAVE_INTERVAL: &'static str = "autosave_interval";
 const KEY_RECENT_FILES: &'static str = "recent_files";
+const KEY_WATCH_IMAGES: &'static str = "watch_images";
 
 pub struct Storage {
+    pub autosave_interval: u64,
     pub recent_files: FixedVec<PathBuf>,
+    pub watch_images: bool,
 }
 
 impl Default for Storage {
     fn default() -> Self {
         Self {
+            autosave_interval: AUTOSAVE_INTERVAL,
             recent_files: FixedVec::new(RECENT_FILE_COUNT),
+            watch_images: false,
         }
     }
 }
 
 impl Storage {
-    pub fn restore(&mut self, storage: &dyn eframe::Storage) -> Result<()> {
-        if let Some(value) = storage.get_string(KEY_RECENT_FILES) {
-            self.recent_files = serde_json::from_str(&value)?;
+    pub fn restore(&mut self, storage: &dyn eframe::Storage) {
+        if let Some(value) = restore_storage_value(storage, KEY_AUTOSAVE_INTERVAL) {
+            self.autosave_interval = value;
+        }
+
+        if let Some(value) = restore_storage_value(storage, KEY_RECENT_FILES) {
+            self.recent_files = value;
 
             self.recent_files
                 .resize(RECENT_FILE_COUNT, PathBuf::default());
         }
 
-        Ok(())
+        if let Some(value) = restore_storage_value(storage, KEY_WATCH_IMAGES) {
+            self.watch_images = value;
+        }
     }
 
     pub fn save(&self, storage: &mut dyn eframe::Storage) -> Result<()> {
+        storage.set_string(
+            KEY_AUTOSAVE_INTERVAL,
+            serde_json::to_string(&self.autosave_interval)?,
+        );
         storage.set_string(KEY_RECENT_FILES, serde_json::to_string(&self.recent_files)?);
+        storage.set_string(KEY_WATCH_IMAGES, serde_json::to_string(&self.watch_images)?);
 
         Ok(())
     }
@@ -2,7 +2,7 @@ use std::ops::RangeInclusive;
 
 use eframe::{
     egui::{
-        Button, CentralPanel, Context, DragValue, Frame, Grid, Layout, ScrollArea, Sense,
+        Button, CentralPanel, Context, DragValue, Frame, Grid, Layout, RichText, ScrollArea, Sense,
         SidePanel, TextEdit, TopBottomPanel, Ui, Window,
     },
     emath::{Align, Align2},
@@ -11,11 +11,13 @@ use eframe::{
 use material_icons::{icon_to_char, Icon};
 use paperdoll_tar::paperdoll::Point;
 
-use crate::common::TextureData;
+use crate::{common::TextureData, validation::ProblemTarget};
 
 use super::{
     actions::Action,
     canvas::CanvasState,
+    guides::GuideOrientation,
+    relink::RelinkStatus,
     widgets::{
         Card, Dialog, DialogResponse, FragmentEntry, ImageUpload, Modal, PivotSelect, SlotEntry,
         Tooltip,
@@ -61,6 +63,16 @@ impl EditorApp {
 
         self.ui_associated_slots_window(ctx);
 
+        self.ui_guides_window(ctx);
+
+        self.ui_history_window(ctx);
+
+        self.ui_problems_window(ctx);
+
+        self.ui_duplicates_window(ctx);
+
+        self.ui_relink_window(ctx);
+
         self.ui_about_window(ctx);
 
         self.ui_dialog(ctx);
@@ -239,6 +251,14 @@ impl EditorApp {
         if let Some(doll) = doll {
             let doll_id = doll.id();
 
+            let doll_props = (
+                doll.desc.clone(),
+                doll.width,
+                doll.height,
+                doll.offset.x,
+                doll.offset.y,
+            );
+
             Grid::new("doll")
                 .num_columns(2)
                 .striped(true)
@@ -342,6 +362,18 @@ impl EditorApp {
                     }
                 });
 
+            if doll_props
+                != (
+                    doll.desc.clone(),
+                    doll.width,
+                    doll.height,
+                    doll.offset.x,
+                    doll.offset.y,
+                )
+            {
+                self.history_pending = Some("Edit doll".to_owned());
+            }
+
             ui.separator();
 
             ui.horizontal(|ui| {
@@ -463,7 +495,8 @@ impl EditorApp {
                                 if let Some(slot) = self.ppd.get_slot(slot_id) {
                                     let is_actived = self
                                         .actived_slot
-                                        .map_or(false, |actived_slot| actived_slot == slot_id);
+                                        .map_or(false, |actived_slot| actived_slot == slot_id)
+                                        || self.selected_slots.contains(&slot_id);
 
                                     ui.horizontal(|ui| {
                                         ui.spacing_mut().item_spacing.x = 0.0;
@@ -554,7 +587,13 @@ impl EditorApp {
                                         let resp = ui.add(SlotEntry::new(slot).actived(is_actived));
 
                                         if resp.clicked() {
-                                            self.actived_slot = Some(slot_id);
+                                            if ui.input(|i| i.modifiers.shift) {
+                                                self.toggle_slot_selection(slot_id);
+                                            } else {
+                                                self.selected_slots.clear();
+
+                                                self.actived_slot = Some(slot_id);
+                                            }
                                         }
 
                                         if resp.double_clicked() {
@@ -576,6 +615,8 @@ impl EditorApp {
                                 .clicked()
                             {
                                 self.actived_slot = None;
+
+                                self.selected_slots.clear();
                             }
                         });
                     });
@@ -820,6 +861,77 @@ impl EditorApp {
         });
     }
 
+    fn ui_duplicates_window(&mut self, ctx: &Context) {
+        if !self.window_duplicates_visible {
+            return;
+        }
+
+        Window::new("Duplicate Fragments")
+            .default_pos(ctx.screen_rect().right_bottom() + vec2(-420.0, -360.0))
+            .default_width(360.0)
+            .open(&mut self.window_duplicates_visible)
+            .show(ctx, |ui| {
+                if self.duplicate_fragments.is_empty() {
+                    ui.label("No duplicate fragments found.");
+
+                    return;
+                }
+
+                ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for group in &self.duplicate_fragments {
+                            ui.group(|ui| {
+                                ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
+                                    for (index, id) in group.iter().enumerate() {
+                                        let desc = self
+                                            .ppd
+                                            .get_fragment(*id)
+                                            .map(|fragment| fragment.desc.as_str())
+                                            .unwrap_or_default();
+
+                                        let text = if index == 0 {
+                                            format!("{} {} (kept)", id, desc)
+                                        } else {
+                                            format!("{} {}", id, desc)
+                                        };
+
+                                        if ui
+                                            .selectable_label(
+                                                self.actived_fragment == Some(*id),
+                                                text,
+                                            )
+                                            .clicked()
+                                        {
+                                            self.actived_fragment = Some(*id);
+                                        }
+                                    }
+
+                                    if ui.button("Merge").clicked() {
+                                        self.actions
+                                            .push_back(Action::DuplicatesMerge(group.clone()));
+                                    }
+                                });
+                            });
+                        }
+                    });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} groups of duplicates found.",
+                        self.duplicate_fragments.len()
+                    ));
+
+                    if ui.button("Merge All").clicked() {
+                        self.actions.push_back(Action::DuplicatesMergeAll);
+                    }
+                });
+            });
+    }
+
     fn ui_fragment_window(&mut self, ctx: &Context) {
         if !self.window_fragment_visible {
             return;
@@ -996,12 +1108,171 @@ impl EditorApp {
         }
     }
 
+    fn ui_guides_window(&mut self, ctx: &Context) {
+        if !self.window_guides_visible {
+            return;
+        }
+
+        Window::new("Guides")
+            .default_pos(ctx.screen_rect().right_top() + vec2(-320.0, 120.0))
+            .default_width(200.0)
+            .open(&mut self.window_guides_visible)
+            .show(ctx, |ui| {
+                let Some(doll_id) = self.actived_doll else {
+                    ui.label("No doll selected.");
+
+                    return;
+                };
+
+                for (orientation, title) in [
+                    (GuideOrientation::Horizontal, "Horizontal"),
+                    (GuideOrientation::Vertical, "Vertical"),
+                ] {
+                    ui.horizontal(|ui| {
+                        ui.strong(title);
+
+                        if ui
+                            .button(icon_to_char(Icon::Add).to_string())
+                            .on_hover_text("Add guide")
+                            .clicked()
+                        {
+                            self.actions
+                                .push_back(Action::GuideAdd(doll_id, orientation));
+                        }
+                    });
+
+                    let guides = self.guides.doll_mut(doll_id).get_mut(orientation);
+
+                    let mut is_changed = false;
+
+                    for (index, value) in guides.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            is_changed |= ui.add(DragValue::new(value).speed(1.0)).changed();
+
+                            if ui
+                                .button(icon_to_char(Icon::Delete).to_string())
+                                .on_hover_text("Delete guide")
+                                .clicked()
+                            {
+                                self.actions.push_back(Action::GuideRemove(
+                                    doll_id,
+                                    orientation,
+                                    index,
+                                ));
+                            }
+                        });
+                    }
+
+                    if is_changed {
+                        self.actions.push_back(Action::GuidesChanged);
+                    }
+
+                    ui.separator();
+                }
+
+                ui.label(
+                    RichText::new(
+                        "Drag from the rulers to create guides, drop them back to delete.",
+                    )
+                    .weak(),
+                );
+            });
+    }
+
+    fn ui_history_window(&mut self, ctx: &Context) {
+        if !self.window_history_visible {
+            return;
+        }
+
+        Window::new("History")
+            .default_pos(ctx.screen_rect().right_top() + vec2(-320.0, 80.0))
+            .default_width(240.0)
+            .open(&mut self.window_history_visible)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            self.history.can_undo(),
+                            Button::new(icon_to_char(Icon::Undo).to_string()),
+                        )
+                        .on_hover_text("Undo")
+                        .clicked()
+                    {
+                        self.actions.push_back(Action::HistoryUndo);
+                    }
+
+                    if ui
+                        .add_enabled(
+                            self.history.can_redo(),
+                            Button::new(icon_to_char(Icon::Redo).to_string()),
+                        )
+                        .on_hover_text("Redo")
+                        .clicked()
+                    {
+                        self.actions.push_back(Action::HistoryRedo);
+                    }
+                });
+
+                ui.group(|ui| {
+                    ScrollArea::vertical()
+                        .auto_shrink([false, false])
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
+                                ui.spacing_mut().item_spacing.y = 0.0;
+
+                                let undo_len = self.history.undo_len();
+
+                                if ui
+                                    .selectable_label(undo_len == 0, "Initial State")
+                                    .clicked()
+                                {
+                                    for _ in 0..undo_len {
+                                        self.actions.push_back(Action::HistoryUndo);
+                                    }
+                                }
+
+                                for (index, entry) in self.history.undo_entries().enumerate() {
+                                    if ui
+                                        .selectable_label(
+                                            index + 1 == undo_len,
+                                            entry.desc.as_str(),
+                                        )
+                                        .clicked()
+                                    {
+                                        for _ in index + 1..undo_len {
+                                            self.actions.push_back(Action::HistoryUndo);
+                                        }
+                                    }
+                                }
+
+                                for (index, entry) in self.history.redo_entries().enumerate() {
+                                    if ui
+                                        .selectable_label(
+                                            false,
+                                            RichText::new(&entry.desc).weak().italics(),
+                                        )
+                                        .clicked()
+                                    {
+                                        for _ in 0..=index {
+                                            self.actions.push_back(Action::HistoryRedo);
+                                        }
+                                    }
+                                }
+                            });
+                        });
+                });
+            });
+    }
+
     fn ui_left_panel(&mut self, ui: &mut Ui) {
         ui.heading("Project Info");
 
         Grid::new("meta").num_columns(2).show(ui, |ui| {
             ui.label("Name:");
-            ui.text_edit_singleline(&mut self.ppd.meta.name);
+            if ui.text_edit_singleline(&mut self.ppd.meta.name).changed() {
+                self.history_pending = Some("Rename project".to_owned());
+            }
         });
 
         ui.separator();
@@ -1011,6 +1282,170 @@ impl EditorApp {
         self.ui_doll(ui);
     }
 
+    fn ui_problems_window(&mut self, ctx: &Context) {
+        if !self.window_problems_visible {
+            return;
+        }
+
+        Window::new("Problems")
+            .default_pos(ctx.screen_rect().right_bottom() + vec2(-420.0, -320.0))
+            .default_width(360.0)
+            .open(&mut self.window_problems_visible)
+            .show(ctx, |ui| {
+                if self.problems.is_empty() {
+                    ui.label("No problems found.");
+
+                    return;
+                }
+
+                ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
+                            for problem in &self.problems {
+                                let is_actived = match problem.target {
+                                    ProblemTarget::Doll(id) => self.actived_doll == Some(id),
+                                    ProblemTarget::Fragment(id) => {
+                                        self.actived_fragment == Some(id)
+                                    }
+                                    ProblemTarget::Slot(id) => self.actived_slot == Some(id),
+                                };
+
+                                let icon = if problem.is_error() {
+                                    Icon::Error
+                                } else {
+                                    Icon::Warning
+                                };
+
+                                let text = format!("{} {}", icon_to_char(icon), problem.message);
+
+                                if ui.selectable_label(is_actived, text).clicked() {
+                                    self.actions
+                                        .push_back(Action::ProblemSelect(problem.target));
+                                }
+                            }
+                        });
+                    });
+            });
+    }
+
+    fn ui_relink_window(&mut self, ctx: &Context) {
+        if !self.window_relink_visible {
+            return;
+        }
+
+        let mut is_open = true;
+
+        Modal::new("relink_window").show(ctx, |ctx| {
+            Window::new("Relink Missing Images")
+                .pivot(Align2::CENTER_CENTER)
+                .default_pos(ctx.screen_rect().center())
+                .collapsible(false)
+                .resizable(false)
+                .open(&mut is_open)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("Choose Root Directory").clicked() {
+                            self.actions.push_back(Action::RelinkSelectRoot);
+                        }
+
+                        if let Some(root) = &self.relink_root {
+                            ui.label(root.to_string_lossy().to_string());
+                        }
+                    });
+
+                    ui.separator();
+
+                    if self.relink_root.is_none() {
+                        ui.label(
+                            RichText::new("Files are matched by name under the chosen directory.")
+                                .weak(),
+                        );
+                    } else if self.relink_entries.is_empty() {
+                        ui.label("No missing images.");
+                    } else {
+                        ScrollArea::vertical()
+                            .auto_shrink([false, true])
+                            .max_height(300.0)
+                            .show(ui, |ui| {
+                                Grid::new("relink")
+                                    .num_columns(3)
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for entry in &self.relink_entries {
+                                            ui.label(&entry.name);
+
+                                            ui.label(&entry.old_path);
+
+                                            match entry.status {
+                                                RelinkStatus::Found => {
+                                                    ui.label(format!(
+                                                        "{} {}",
+                                                        icon_to_char(Icon::Check),
+                                                        entry
+                                                            .new_path
+                                                            .as_ref()
+                                                            .map(|path| path
+                                                                .to_string_lossy()
+                                                                .to_string())
+                                                            .unwrap_or_default()
+                                                    ));
+                                                }
+                                                RelinkStatus::Missing => {
+                                                    ui.label(format!(
+                                                        "{} Missing",
+                                                        icon_to_char(Icon::Close)
+                                                    ));
+                                                }
+                                                RelinkStatus::SizeChanged(
+                                                    (w, h),
+                                                    (new_w, new_h),
+                                                ) => {
+                                                    ui.label(format!(
+                                                        "{} Size changed {}x{} → {}x{}",
+                                                        icon_to_char(Icon::Warning),
+                                                        w,
+                                                        h,
+                                                        new_w,
+                                                        new_h
+                                                    ));
+                                                }
+                                            }
+
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+                    }
+
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(
+                                self.relink_entries
+                                    .iter()
+                                    .any(|entry| entry.new_path.is_some()),
+                                Button::new("Relink"),
+                            )
+                            .clicked()
+                        {
+                            self.actions.push_back(Action::RelinkConfirm);
+                        }
+
+                        if ui.button("Cancel").clicked() {
+                            self.actions.push_back(Action::WindowRelinkVisible(false));
+                        }
+                    });
+                });
+        });
+
+        if !is_open {
+            self.actions.push_back(Action::WindowRelinkVisible(false));
+        }
+    }
+
     fn ui_right_panel(&mut self, ui: &mut Ui) {
         ui.vertical(|ui| {
             ui.horizontal(|ui| {
@@ -1880,6 +2315,37 @@ impl EditorApp {
                 ui.label(format!("{}%", self.viewport.scale * 100.0));
             });
 
+            ui.add_visible_ui(true, |ui| {
+                ui.set_width(80.0);
+
+                let text = format!("{} {}", icon_to_char(Icon::Warning), self.problems.len());
+
+                let text = if self.problems.is_empty() {
+                    RichText::new(text).weak()
+                } else if self.problems.iter().any(|problem| problem.is_error()) {
+                    RichText::new(text).color(ui.visuals().error_fg_color)
+                } else {
+                    RichText::new(text).color(ui.visuals().warn_fg_color)
+                };
+
+                if ui
+                    .add(Button::new(text).frame(false))
+                    .on_hover_text("Problems")
+                    .clicked()
+                {
+                    self.actions
+                        .push_back(Action::WindowProblemsVisible(!self.window_problems_visible));
+                }
+            });
+
+            if let Some((text, expire_time)) = &self.notification {
+                if ui.input(|i| i.time) < *expire_time {
+                    ui.strong(format!("{} {}", icon_to_char(Icon::Info), text));
+
+                    ui.separator();
+                }
+            }
+
             match self.canvas_state {
                 CanvasState::Idle | CanvasState::Dragging => ui.horizontal_wrapped(|ui| {
                     ui.strong("Ctrl + Scroll");
@@ -1890,6 +2356,12 @@ impl EditorApp {
                     ui.strong("Left Click");
                     ui.label("to select a slot");
 
+                    ui.strong("Shift + Left Click");
+                    ui.label("to add / remove a slot from the selection");
+
+                    ui.strong("Left Drag");
+                    ui.label("to select slots in a box");
+
                     ui.strong("Right Drag");
                     ui.label("to drag around");
 
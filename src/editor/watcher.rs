@@ -0,0 +1,161 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use eframe::egui::Context;
+use paperdoll_tar::paperdoll::{ColorType, ImageData};
+
+use crate::{common::upload_image_to_texture, fs::open_image_rgba};
+
+use super::{actions::Action, EditorApp};
+
+const WATCH_INTERVAL: f64 = 1.0;
+
+#[derive(Default)]
+pub(super) struct ImageWatcher {
+    check_time: f64,
+    modified_times: HashMap<PathBuf, SystemTime>,
+}
+
+impl ImageWatcher {
+    pub fn reset(&mut self) {
+        self.modified_times.clear();
+    }
+
+    // files seen for the first time are only recorded
+    fn is_modified(&mut self, path: &Path) -> bool {
+        let Ok(modified_time) = fs::metadata(path).and_then(|metadata| metadata.modified()) else {
+            return false;
+        };
+
+        self.modified_times
+            .insert(path.to_path_buf(), modified_time)
+            .map_or(false, |previous| previous != modified_time)
+    }
+}
+
+impl EditorApp {
+    pub(super) fn watch_images(&mut self, ctx: &Context) {
+        if !self.storage.watch_images {
+            return;
+        }
+
+        let now = ctx.input(|i| i.time);
+        let elapsed = now - self.image_watcher.check_time;
+
+        if elapsed < WATCH_INTERVAL {
+            ctx.request_repaint_after(Duration::from_secs_f64(WATCH_INTERVAL - elapsed));
+
+            return;
+        }
+
+        self.image_watcher.check_time = now;
+
+        ctx.request_repaint_after(Duration::from_secs_f64(WATCH_INTERVAL));
+
+        let paths: HashSet<PathBuf> = self
+            .ppd
+            .dolls()
+            .map(|(_, doll)| doll.path.as_str())
+            .chain(
+                self.ppd
+                    .fragments()
+                    .map(|(_, fragment)| fragment.path.as_str()),
+            )
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect();
+
+        for path in paths {
+            if self.image_watcher.is_modified(&path) {
+                self.actions.push_back(Action::WatchedImageChanged(path));
+            }
+        }
+    }
+
+    // reloads every doll background and fragment which comes from the path
+    pub(super) fn reload_images_from_path(&mut self, path: &Path, ctx: &Context) -> Result<usize> {
+        let path_str = path.to_string_lossy().to_string();
+
+        let doll_ids: Vec<u32> = self
+            .ppd
+            .dolls()
+            .filter(|(_, doll)| doll.path == path_str)
+            .map(|(id, _)| *id)
+            .collect();
+        let fragment_ids: Vec<u32> = self
+            .ppd
+            .fragments()
+            .filter(|(_, fragment)| fragment.path == path_str)
+            .map(|(id, _)| *id)
+            .collect();
+
+        if doll_ids.is_empty() && fragment_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let image = open_image_rgba(path)?;
+
+        for id in &doll_ids {
+            self.set_doll_image(*id, path, &image, ctx);
+        }
+
+        for id in &fragment_ids {
+            self.set_fragment_image(*id, path, &image, ctx);
+        }
+
+        Ok(doll_ids.len() + fragment_ids.len())
+    }
+
+    pub(super) fn reload_doll_image(&mut self, id: u32, path: &Path, ctx: &Context) -> Result<()> {
+        let image = open_image_rgba(path)?;
+
+        self.set_doll_image(id, path, &image, ctx);
+
+        Ok(())
+    }
+
+    pub(super) fn reload_fragment_image(
+        &mut self,
+        id: u32,
+        path: &Path,
+        ctx: &Context,
+    ) -> Result<()> {
+        let image = open_image_rgba(path)?;
+
+        self.set_fragment_image(id, path, &image, ctx);
+
+        Ok(())
+    }
+
+    fn set_doll_image(&mut self, id: u32, path: &Path, image: &ImageData, ctx: &Context) {
+        let texture = upload_image_to_texture(image, "doll", ctx);
+
+        if let Some(doll) = self.ppd.get_doll_mut(id) {
+            doll.path = path.to_string_lossy().to_string();
+
+            doll.image.width = image.width;
+            doll.image.height = image.height;
+            doll.image.color_type = ColorType::Rgba;
+            doll.image.pixels = image.pixels.clone();
+
+            self.textures_doll.insert(id, texture);
+        }
+    }
+
+    // pivots and the slots using the fragment are kept as they are
+    fn set_fragment_image(&mut self, id: u32, path: &Path, image: &ImageData, ctx: &Context) {
+        let texture = upload_image_to_texture(image, "fragment", ctx);
+
+        self.actions.push_back(Action::FragmentUpdateTexture(
+            id,
+            path.to_path_buf(),
+            texture,
+            image.pixels.clone(),
+        ));
+    }
+}
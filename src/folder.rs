@@ -0,0 +1,136 @@
+use std::{
+    fs, mem,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use paperdoll_tar::paperdoll::{ColorType, ImageData, Manifest, PaperdollFactory};
+
+use crate::fs::open_image_rgba;
+
+const MANIFEST_FILE: &'static str = "manifest.json";
+const DOLLS_DIR: &'static str = "dolls";
+const FRAGMENTS_DIR: &'static str = "fragments";
+
+pub fn pack_folder<P>(dir: P) -> Result<Manifest>
+where
+    P: AsRef<Path>,
+{
+    let dir = dir.as_ref();
+
+    let manifest_path = dir.join(MANIFEST_FILE);
+
+    if !manifest_path.exists() {
+        return Err(anyhow!("{} not found in {}.", MANIFEST_FILE, dir.display()));
+    }
+
+    let mut manifest: Manifest = serde_json::from_str(&fs::read_to_string(manifest_path)?)?;
+
+    for doll in manifest.dolls.iter_mut() {
+        let path = image_path(dir, DOLLS_DIR, doll.id());
+
+        if path.exists() {
+            doll.image = open_image_rgba(path)?;
+        }
+    }
+
+    for fragment in manifest.fragments.iter_mut() {
+        let path = image_path(dir, FRAGMENTS_DIR, fragment.id());
+
+        if path.exists() {
+            fragment.image = open_image_rgba(path)?;
+        }
+    }
+
+    Ok(manifest)
+}
+
+pub fn unpack_to_folder<P>(ppd: &PaperdollFactory, dir: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let dir = dir.as_ref();
+
+    fs::create_dir_all(dir.join(DOLLS_DIR))?;
+    fs::create_dir_all(dir.join(FRAGMENTS_DIR))?;
+
+    let mut manifest = ppd.to_manifest();
+
+    // images are written as png files and kept out of the manifest
+    for doll in manifest.dolls.iter_mut() {
+        let image = take_image(&mut doll.image);
+
+        write_image(&image, &image_path(dir, DOLLS_DIR, doll.id()))?;
+    }
+
+    for fragment in manifest.fragments.iter_mut() {
+        let image = take_image(&mut fragment.image);
+
+        write_image(&image, &image_path(dir, FRAGMENTS_DIR, fragment.id()))?;
+    }
+
+    fs::write(
+        dir.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    Ok(())
+}
+
+fn image_path(dir: &Path, kind: &str, id: u32) -> PathBuf {
+    dir.join(kind).join(format!("{}.png", id))
+}
+
+fn take_image(image: &mut ImageData) -> ImageData {
+    ImageData {
+        width: mem::take(&mut image.width),
+        height: mem::take(&mut image.height),
+        color_type: mem::replace(&mut image.color_type, ColorType::Rgba),
+        pixels: mem::take(&mut image.pixels),
+    }
+}
+
+fn write_image(image: &ImageData, path: &Path) -> Result<()> {
+    // a previously unpacked image is removed when there is no image anymore
+    if image.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+
+        return Ok(());
+    }
+
+    image::save_buffer(
+        path,
+        &image.pixels,
+        image.width,
+        image.height,
+        color_type(image)?,
+    )?;
+
+    Ok(())
+}
+
+// the buffer layout is told by the number of bytes per pixel
+fn color_type(image: &ImageData) -> Result<image::ColorType> {
+    let pixel_count = image.width as usize * image.height as usize;
+
+    let channels = if pixel_count == 0 {
+        0
+    } else {
+        image.pixels.len() / pixel_count
+    };
+
+    match channels {
+        1 => Ok(image::ColorType::L8),
+        2 => Ok(image::ColorType::La8),
+        3 => Ok(image::ColorType::Rgb8),
+        4 => Ok(image::ColorType::Rgba8),
+        _ => Err(anyhow!(
+            "Unsupported image of {} bytes for {}x{} pixels.",
+            image.pixels.len(),
+            image.width,
+            image.height
+        )),
+    }
+}
@@ -18,6 +18,47 @@ pub fn create_file(filename: &str) -> Option<PathBuf> {
         .save_file()
 }
 
+pub fn export_animation(filename: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .set_directory("~")
+        .add_filter("Animations", &["gif", "png"])
+        .set_file_name(filename)
+        .save_file()
+}
+
+pub fn export_json(filename: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .set_directory("~")
+        .add_filter("JSON", &["json"])
+        .set_file_name(filename)
+        .save_file()
+}
+
+pub fn export_rpy(filename: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .set_directory("~")
+        .add_filter("Ren'Py Script", &["rpy"])
+        .set_file_name(filename)
+        .save_file()
+}
+
+pub fn export_tscn(filename: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .set_directory("~")
+        .add_filter("Godot Scene", &["tscn"])
+        .set_file_name(filename)
+        .save_file()
+}
+
+// sprite sheets keep their alpha channel, so formats without one are left out
+pub fn export_sprite_sheet(filename: &str) -> Option<PathBuf> {
+    FileDialog::new()
+        .set_directory("~")
+        .add_filter("Images", &["png", "webp"])
+        .set_file_name(filename)
+        .save_file()
+}
+
 pub fn export_texture(filename: &str) -> Option<PathBuf> {
     FileDialog::new()
         .set_directory("~")
@@ -49,6 +90,24 @@ pub fn select_file() -> Option<PathBuf> {
         .pick_file()
 }
 
+pub fn select_folder() -> Option<PathBuf> {
+    FileDialog::new().set_directory("~").pick_folder()
+}
+
+pub fn select_json() -> Option<PathBuf> {
+    FileDialog::new()
+        .set_directory("~")
+        .add_filter("JSON", &["json"])
+        .pick_file()
+}
+
+pub fn select_ora() -> Option<PathBuf> {
+    FileDialog::new()
+        .set_directory("~")
+        .add_filter("OpenRaster", &["ora"])
+        .pick_file()
+}
+
 pub fn select_texture() -> Option<PathBuf> {
     FileDialog::new()
         .set_directory("~")
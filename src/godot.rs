@@ -0,0 +1,297 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    fs,
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use paperdoll_tar::paperdoll::{ImageData, PaperdollFactory};
+
+const DOLL_SCRIPT: &'static str = r#"@tool
+extends Node2D
+
+# Shows the candidate at `index` in the slot node named `slot`, a negative index empties the slot.
+func set_candidate(slot: String, index: int) -> void:
+	var node := get_node_or_null(NodePath(slot))
+	if node == null:
+		return
+
+	var candidates: Array = node.get_meta("candidates", [])
+	var candidate = null
+	if index >= 0 and index < candidates.size():
+		candidate = candidates[index]
+
+	for sprite in node.get_children():
+		if candidate == null:
+			sprite.visible = false
+			continue
+
+		var texture: Texture2D = candidate["texture"]
+
+		sprite.visible = true
+		sprite.texture = texture
+
+		if node.get_meta("constrainted", false):
+			sprite.offset = Vector2.ZERO
+			sprite.scale = node.get_meta("size") / texture.get_size()
+		else:
+			sprite.offset = -candidate["pivot"]
+			sprite.scale = Vector2.ONE
+"#;
+
+// writes the scene to `path`, a script next to it and images into a folder named after the scene
+pub fn export_godot<P>(ppd: &PaperdollFactory, doll_id: u32, path: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let doll = ppd
+        .get_doll(doll_id)
+        .ok_or_else(|| anyhow!("Doll {} not found.", doll_id))?;
+
+    let path = path.as_ref().with_extension("tscn");
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow!("Invalid scene path: {}", path.display()))?;
+
+    let image_dir = path.with_file_name(&stem);
+
+    fs::create_dir_all(&image_dir)?;
+
+    let script_name = format!("{}.gd", stem);
+
+    fs::write(path.with_file_name(&script_name), DOLL_SCRIPT)?;
+
+    // paths of resources are relative to the scene
+    let mut resources = String::new();
+    let mut resource_ids: HashMap<String, usize> = HashMap::new();
+
+    let mut add_resource = |resource_type: &str, resource_path: String| -> Result<usize> {
+        if let Some(id) = resource_ids.get(&resource_path) {
+            return Ok(*id);
+        }
+
+        let id = resource_ids.len() + 1;
+
+        writeln!(
+            resources,
+            "[ext_resource type=\"{}\" path=\"{}\" id=\"{}\"]",
+            resource_type, resource_path, id
+        )?;
+
+        resource_ids.insert(resource_path, id);
+
+        Ok(id)
+    };
+
+    let script_id = add_resource("Script", script_name)?;
+
+    let mut nodes = String::new();
+
+    writeln!(
+        nodes,
+        "[node name=\"{}\" type=\"Node2D\"]",
+        node_name(&doll.desc, "Doll", doll_id)
+    )?;
+    writeln!(nodes, "script = ExtResource(\"{}\")", script_id)?;
+
+    let mut node_names = HashSet::new();
+
+    if !doll.image.is_empty() {
+        let file_name = format!("doll_{}.png", doll_id);
+
+        write_image(&doll.image, &image_dir.join(&file_name))?;
+
+        let texture_id = add_resource("Texture2D", format!("{}/{}", stem, file_name))?;
+
+        node_names.insert("Background".to_owned());
+
+        writeln!(nodes)?;
+        writeln!(
+            nodes,
+            "[node name=\"Background\" type=\"Sprite2D\" parent=\".\"]"
+        )?;
+        writeln!(nodes, "texture = ExtResource(\"{}\")", texture_id)?;
+        writeln!(nodes, "centered = false")?;
+    }
+
+    let mut written_fragments = HashSet::new();
+
+    // z-order follows the order of slots in the doll
+    for (z_index, slot_id) in doll.slots.iter().enumerate() {
+        let Some(slot) = ppd.get_slot(*slot_id) else {
+            continue;
+        };
+
+        let mut candidates = vec![];
+
+        for fragment_id in &slot.candidates {
+            let Some(fragment) = ppd.get_fragment(*fragment_id) else {
+                continue;
+            };
+
+            if fragment.image.is_empty() {
+                continue;
+            }
+
+            let file_name = format!("fragment_{}.png", fragment_id);
+
+            if written_fragments.insert(*fragment_id) {
+                write_image(&fragment.image, &image_dir.join(&file_name))?;
+            }
+
+            let texture_id = add_resource("Texture2D", format!("{}/{}", stem, file_name))?;
+
+            candidates.push((fragment, texture_id));
+        }
+
+        let slot_name = unique_node_name(&slot.desc, "Slot", *slot_id, &mut node_names);
+
+        let candidates_meta: Vec<String> = candidates
+            .iter()
+            .map(|(fragment, texture_id)| {
+                format!(
+                    "{{\"id\": {}, \"name\": \"{}\", \"texture\": ExtResource(\"{}\"), \"pivot\": Vector2({}, {})}}",
+                    fragment.id(),
+                    escape(&fragment.desc),
+                    texture_id,
+                    fragment.pivot.x,
+                    fragment.pivot.y
+                )
+            })
+            .collect();
+
+        writeln!(nodes)?;
+        writeln!(
+            nodes,
+            "[node name=\"{}\" type=\"Node2D\" parent=\".\"]",
+            slot_name
+        )?;
+        writeln!(nodes, "z_index = {}", z_index)?;
+        writeln!(nodes, "metadata/id = {}", slot_id)?;
+        writeln!(nodes, "metadata/required = {}", slot.required)?;
+        writeln!(nodes, "metadata/constrainted = {}", slot.constrainted)?;
+        writeln!(
+            nodes,
+            "metadata/size = Vector2({}, {})",
+            slot.width, slot.height
+        )?;
+        writeln!(
+            nodes,
+            "metadata/candidates = [{}]",
+            candidates_meta.join(", ")
+        )?;
+
+        // required slots show their first candidate, others start empty
+        let candidate = slot.required.then(|| candidates.first()).flatten();
+
+        for (index, position) in slot.positions.iter().enumerate() {
+            writeln!(nodes)?;
+            writeln!(
+                nodes,
+                "[node name=\"Position{}\" type=\"Sprite2D\" parent=\"{}\"]",
+                index, slot_name
+            )?;
+
+            if slot.constrainted {
+                writeln!(nodes, "position = Vector2({}, {})", position.x, position.y)?;
+            } else {
+                writeln!(
+                    nodes,
+                    "position = Vector2({}, {})",
+                    position.x + slot.anchor.x,
+                    position.y + slot.anchor.y
+                )?;
+            }
+
+            writeln!(nodes, "centered = false")?;
+
+            match candidate {
+                Some((fragment, texture_id)) => {
+                    writeln!(nodes, "texture = ExtResource(\"{}\")", texture_id)?;
+
+                    if slot.constrainted {
+                        writeln!(
+                            nodes,
+                            "scale = Vector2({}, {})",
+                            slot.width as f32 / fragment.image.width as f32,
+                            slot.height as f32 / fragment.image.height as f32
+                        )?;
+                    } else {
+                        writeln!(
+                            nodes,
+                            "offset = Vector2({}, {})",
+                            -fragment.pivot.x, -fragment.pivot.y
+                        )?;
+                    }
+                }
+                None => {
+                    writeln!(nodes, "visible = false")?;
+                }
+            }
+        }
+    }
+
+    let mut scene = String::new();
+
+    writeln!(
+        scene,
+        "[gd_scene load_steps={} format=3]",
+        resource_ids.len() + 1
+    )?;
+    writeln!(scene)?;
+    write!(scene, "{}", resources)?;
+    writeln!(scene)?;
+    write!(scene, "{}", nodes)?;
+
+    fs::write(&path, scene)?;
+
+    Ok(())
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// node names can not contain some characters, they fall back to `<prefix><id>`
+fn node_name(desc: &str, prefix: &str, id: u32) -> String {
+    let name: String = desc
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '.' | ':' | '@' | '/' | '"' | '%'))
+        .collect();
+
+    if name.is_empty() {
+        format!("{}{}", prefix, id)
+    } else {
+        name
+    }
+}
+
+fn unique_node_name(desc: &str, prefix: &str, id: u32, names: &mut HashSet<String>) -> String {
+    let name = node_name(desc, prefix, id);
+
+    let name = if names.contains(&name) {
+        format!("{}{}", name, id)
+    } else {
+        name
+    };
+
+    names.insert(name.clone());
+
+    name
+}
+
+fn write_image(image: &ImageData, path: &Path) -> Result<()> {
+    image::save_buffer(
+        path,
+        &image.pixels,
+        image.width,
+        image.height,
+        image::ColorType::Rgba8,
+    )?;
+
+    Ok(())
+}
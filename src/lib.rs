@@ -1,8 +1,19 @@
+pub mod cli;
 pub mod editor;
 pub mod viewer;
 
 mod adapter;
+mod atlas;
 mod common;
+mod duplicates;
 mod fixed_vec;
+mod folder;
 mod fs;
+mod godot;
+mod ora;
+mod renpy;
+mod sheet;
+#[cfg(test)]
+mod test_utils;
+mod validation;
 mod viewport;
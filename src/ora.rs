@@ -0,0 +1,225 @@
+use std::{fs::File, io::Read, path::Path};
+
+use anyhow::{anyhow, Result};
+use image::{imageops, RgbaImage};
+use paperdoll_tar::paperdoll::{ColorType, ImageData};
+use xml::reader::{EventReader, XmlEvent};
+use zip::ZipArchive;
+
+const STACK_FILE: &'static str = "stack.xml";
+
+pub struct OraLayer {
+    pub name: String,
+    // name of the innermost group containing the layer
+    pub group: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub image: RgbaImage,
+}
+
+pub struct OraGroup {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub layers: Vec<OraLayer>,
+}
+
+pub struct OraFile {
+    pub width: u32,
+    pub height: u32,
+    // layers are listed from top to bottom, as in stack.xml
+    pub layers: Vec<OraLayer>,
+}
+
+impl OraFile {
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let mut archive = ZipArchive::new(File::open(path)?)?;
+
+        let mut stack = String::new();
+        archive.by_name(STACK_FILE)?.read_to_string(&mut stack)?;
+
+        let mut width = 0;
+        let mut height = 0;
+
+        let mut groups: Vec<Option<String>> = vec![];
+        let mut layers = vec![];
+
+        for event in EventReader::new(stack.as_bytes()) {
+            match event? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    let attribute = |key: &str| {
+                        attributes
+                            .iter()
+                            .find(|attribute| attribute.name.local_name == key)
+                            .map(|attribute| attribute.value.as_str())
+                    };
+
+                    let number =
+                        |key: &str| attribute(key).and_then(|value| value.parse::<i32>().ok());
+
+                    match name.local_name.as_str() {
+                        "image" => {
+                            width = number("w").unwrap_or_default().max(0) as u32;
+                            height = number("h").unwrap_or_default().max(0) as u32;
+                        }
+                        "stack" => {
+                            // the root stack is not a group
+                            let group = if groups.is_empty() {
+                                None
+                            } else {
+                                attribute("name").map(|name| name.to_owned())
+                            };
+
+                            groups.push(group);
+                        }
+                        "layer" => {
+                            if attribute("visibility") == Some("hidden") {
+                                continue;
+                            }
+
+                            let Some(src) = attribute("src") else {
+                                continue;
+                            };
+
+                            let mut buffer = vec![];
+                            archive.by_name(src)?.read_to_end(&mut buffer)?;
+
+                            let image = image::load_from_memory(&buffer)?.into_rgba8();
+
+                            layers.push(OraLayer {
+                                name: attribute("name").unwrap_or(src).to_owned(),
+                                group: groups.iter().rev().find_map(|group| group.clone()),
+                                x: number("x").unwrap_or_default(),
+                                y: number("y").unwrap_or_default(),
+                                image,
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+                XmlEvent::EndElement { name } => {
+                    if name.local_name == "stack" {
+                        groups.pop();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if width == 0 || height == 0 {
+            return Err(anyhow!("Invalid image size in {}.", STACK_FILE));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            layers,
+        })
+    }
+
+    // splits layers into groups and the ungrouped ones,
+    // ungrouped layers are padded to the canvas to keep their offsets
+    pub fn into_groups(self) -> (Vec<OraGroup>, Vec<OraLayer>) {
+        let mut groups: Vec<OraGroup> = vec![];
+        let mut ungrouped = vec![];
+
+        for mut layer in self.layers {
+            let Some(group_name) = layer.group.clone() else {
+                layer.pad_to(0, 0, self.width, self.height);
+
+                ungrouped.push(layer);
+
+                continue;
+            };
+
+            match groups.iter_mut().find(|group| group.name == group_name) {
+                Some(group) => group.layers.push(layer),
+                None => groups.push(OraGroup {
+                    name: group_name,
+                    x: 0,
+                    y: 0,
+                    width: 0,
+                    height: 0,
+                    layers: vec![layer],
+                }),
+            }
+        }
+
+        for group in groups.iter_mut() {
+            let min_x = group.layers.iter().map(|layer| layer.x).min().unwrap_or(0);
+            let min_y = group.layers.iter().map(|layer| layer.y).min().unwrap_or(0);
+            let max_x = group
+                .layers
+                .iter()
+                .map(|layer| layer.x + layer.image.width() as i32)
+                .max()
+                .unwrap_or(0);
+            let max_y = group
+                .layers
+                .iter()
+                .map(|layer| layer.y + layer.image.height() as i32)
+                .max()
+                .unwrap_or(0);
+
+            group.x = min_x;
+            group.y = min_y;
+            group.width = (max_x - min_x).max(1) as u32;
+            group.height = (max_y - min_y).max(1) as u32;
+
+            // candidates of a slot share its size, so layers are padded to the group bounds
+            for layer in group.layers.iter_mut() {
+                layer.pad_to(min_x, min_y, group.width, group.height);
+            }
+        }
+
+        (groups, ungrouped)
+    }
+
+    // every layer padded to the canvas, so that fragments keep the layer offsets
+    pub fn into_layers(self) -> Vec<OraLayer> {
+        let (width, height) = (self.width, self.height);
+
+        self.layers
+            .into_iter()
+            .map(|mut layer| {
+                layer.pad_to(0, 0, width, height);
+
+                layer
+            })
+            .collect()
+    }
+}
+
+impl OraLayer {
+    // places the layer in a transparent image of the given bounds
+    fn pad_to(&mut self, x: i32, y: i32, width: u32, height: u32) {
+        let mut image = RgbaImage::new(width, height);
+
+        imageops::replace(
+            &mut image,
+            &self.image,
+            (self.x - x) as i64,
+            (self.y - y) as i64,
+        );
+
+        self.image = image;
+        self.x = x;
+        self.y = y;
+    }
+
+    pub fn to_image_data(&self) -> ImageData {
+        ImageData {
+            width: self.image.width(),
+            height: self.image.height(),
+            color_type: ColorType::Rgba,
+            pixels: self.image.as_raw().clone(),
+        }
+    }
+}
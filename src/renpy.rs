@@ -0,0 +1,278 @@
+use std::{collections::HashSet, fmt::Write, fs, path::Path};
+
+use anyhow::{anyhow, Result};
+use paperdoll_tar::paperdoll::{Fragment, ImageData, PaperdollFactory, Slot};
+
+use crate::common::sorted_ids;
+
+const INDENT: &'static str = "    ";
+
+// writes the script to `path` and images into a folder named after the script next to it,
+// the script is expected to be placed in the `game` directory of the Ren'Py project
+pub fn export_renpy<P>(ppd: &PaperdollFactory, path: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref().with_extension("rpy");
+
+    let image_dir_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow!("Invalid script path: {}", path.display()))?;
+
+    let image_dir = path.with_file_name(&image_dir_name);
+
+    fs::create_dir_all(&image_dir)?;
+
+    let mut script = String::new();
+
+    writeln!(
+        script,
+        "# Generated by {} v{}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )?;
+
+    let mut doll_names = HashSet::new();
+    let mut written_fragments = HashSet::new();
+
+    for doll_id in sorted_ids(ppd.dolls().map(|(id, _)| *id)) {
+        let Some(doll) = ppd.get_doll(doll_id) else {
+            continue;
+        };
+
+        let doll_name = unique_name(&doll.desc, "doll", doll_id, &mut doll_names);
+
+        writeln!(script)?;
+        writeln!(script, "layeredimage {}:", doll_name)?;
+
+        if !doll.image.is_empty() {
+            let file_name = format!("doll_{}.png", doll_id);
+
+            write_image(&doll.image, &image_dir.join(&file_name))?;
+
+            writeln!(script)?;
+            writeln!(script, "{}always:", INDENT)?;
+            writeln!(
+                script,
+                "{}\"{}/{}\"",
+                INDENT.repeat(2),
+                image_dir_name,
+                file_name
+            )?;
+        }
+
+        // attribute names are unique within a layered image
+        let mut names = HashSet::new();
+
+        for slot_id in &doll.slots {
+            let Some(slot) = ppd.get_slot(*slot_id) else {
+                continue;
+            };
+
+            let group_name = unique_name(&slot.desc, "slot", *slot_id, &mut names);
+
+            writeln!(script)?;
+            writeln!(script, "{}group {}:", INDENT, group_name)?;
+
+            let mut is_first = true;
+
+            for fragment_id in &slot.candidates {
+                let Some(fragment) = ppd.get_fragment(*fragment_id) else {
+                    continue;
+                };
+
+                if fragment.image.is_empty() {
+                    continue;
+                }
+
+                let file_name = format!("fragment_{}.png", fragment_id);
+
+                // fragments shared by several slots are only written once
+                if written_fragments.insert(*fragment_id) {
+                    write_image(&fragment.image, &image_dir.join(&file_name))?;
+                }
+
+                let attribute_name =
+                    unique_name(&fragment.desc, "fragment", *fragment_id, &mut names);
+
+                writeln!(script)?;
+                writeln!(
+                    script,
+                    "{}attribute {}{}:",
+                    INDENT.repeat(2),
+                    attribute_name,
+                    if slot.required && is_first {
+                        " default"
+                    } else {
+                        ""
+                    }
+                )?;
+
+                write_displayable(
+                    &mut script,
+                    &format!("{}/{}", image_dir_name, file_name),
+                    slot,
+                    fragment,
+                    (doll.width, doll.height),
+                )?;
+
+                is_first = false;
+            }
+        }
+    }
+
+    fs::write(&path, script)?;
+
+    Ok(())
+}
+
+fn write_displayable(
+    script: &mut String,
+    image_path: &str,
+    slot: &Slot,
+    fragment: &Fragment,
+    doll_size: (u32, u32),
+) -> Result<()> {
+    let indent = INDENT.repeat(3);
+
+    // placement of the fragment follows the canvas of the editor
+    let placements: Vec<(i32, i32)> = slot
+        .positions
+        .iter()
+        .map(|position| {
+            if slot.constrainted {
+                (position.x.round() as i32, position.y.round() as i32)
+            } else {
+                (
+                    (position.x + slot.anchor.x - fragment.pivot.x).round() as i32,
+                    (position.y + slot.anchor.y - fragment.pivot.y).round() as i32,
+                )
+            }
+        })
+        .collect();
+
+    let child = if slot.constrainted {
+        format!(
+            "Transform(\"{}\", xysize=({}, {}))",
+            image_path, slot.width, slot.height
+        )
+    } else {
+        format!("\"{}\"", image_path)
+    };
+
+    match placements.as_slice() {
+        [(x, y)] if !slot.constrainted => {
+            writeln!(script, "{}{}", indent, child)?;
+            writeln!(script, "{}pos ({}, {})", indent, x, y)?;
+        }
+        _ => {
+            let children: Vec<String> = placements
+                .iter()
+                .map(|(x, y)| format!("({}, {}), {}", x, y, child))
+                .collect();
+
+            writeln!(
+                script,
+                "{}Composite(({}, {}), {})",
+                indent,
+                doll_size.0,
+                doll_size.1,
+                children.join(", ")
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+// turns a description into a Ren'Py name, falling back to `<prefix>_<id>`
+fn unique_name(desc: &str, prefix: &str, id: u32, names: &mut HashSet<String>) -> String {
+    let name: String = desc
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    let name = name.trim_matches('_').to_owned();
+
+    let name = if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("{}_{}", prefix, id)
+    } else {
+        name
+    };
+
+    let mut unique = name.clone();
+    let mut count = 0;
+
+    // the id suffix itself can be taken by another description
+    while names.contains(&unique) {
+        unique = if count == 0 {
+            format!("{}_{}", name, id)
+        } else {
+            format!("{}_{}_{}", name, id, count)
+        };
+
+        count += 1;
+    }
+
+    names.insert(unique.clone());
+
+    unique
+}
+
+fn write_image(image: &ImageData, path: &Path) -> Result<()> {
+    image::save_buffer(
+        path,
+        &image.pixels,
+        image.width,
+        image.height,
+        image::ColorType::Rgba8,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::unique_name;
+
+    #[test]
+    fn sanitizes_descriptions() {
+        let mut names = HashSet::new();
+
+        assert_eq!(unique_name("  Red Hat! ", "hat", 1, &mut names), "red_hat");
+        assert_eq!(unique_name("Über-Coat", "coat", 2, &mut names), "ber_coat");
+    }
+
+    #[test]
+    fn falls_back_to_prefix_and_id() {
+        let mut names = HashSet::new();
+
+        assert_eq!(unique_name("", "fragment", 3, &mut names), "fragment_3");
+        assert_eq!(unique_name("___", "fragment", 4, &mut names), "fragment_4");
+        assert_eq!(unique_name("2nd layer", "slot", 5, &mut names), "slot_5");
+    }
+
+    #[test]
+    fn appends_id_to_taken_names() {
+        let mut names = HashSet::new();
+
+        assert_eq!(unique_name("hat", "slot", 1, &mut names), "hat");
+        assert_eq!(unique_name("Hat", "slot", 2, &mut names), "hat_2");
+        assert_eq!(unique_name("hat", "slot", 2, &mut names), "hat_2_1");
+    }
+
+    #[test]
+    fn avoids_names_taken_by_other_descriptions() {
+        let mut names = HashSet::new();
+
+        assert_eq!(unique_name("hat_7", "slot", 1, &mut names), "hat_7");
+        assert_eq!(unique_name("hat", "slot", 2, &mut names), "hat");
+        assert_eq!(unique_name("hat", "slot", 7, &mut names), "hat_7_1");
+        assert_eq!(names.len(), 3);
+    }
+}
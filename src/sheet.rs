@@ -0,0 +1,361 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use anyhow::{anyhow, bail, Result};
+use image::{imageops, RgbaImage};
+use paperdoll_tar::paperdoll::{Paperdoll, PaperdollFactory};
+use serde::Serialize;
+
+use crate::common::save_image;
+
+pub const MAX_COMBINATIONS: usize = 1024;
+pub const MAX_SHEET_PIXELS: u64 = 8192 * 8192;
+
+pub struct SheetSlot {
+    pub slot: u32,
+    // all candidates of the slot are used when not specified
+    pub candidates: Option<Vec<u32>>,
+}
+
+#[derive(Serialize)]
+pub struct SheetCell {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    // ordered so that the index file is stable between exports
+    pub slot_map: BTreeMap<u32, u32>,
+}
+
+#[derive(Serialize)]
+pub struct SheetIndex {
+    pub doll: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub cells: Vec<SheetCell>,
+}
+
+pub fn combinations(
+    ppd: &PaperdollFactory,
+    doll_id: u32,
+    slots: &[SheetSlot],
+    fixed: &HashMap<u32, u32>,
+    limit: usize,
+) -> Result<Vec<HashMap<u32, u32>>> {
+    let doll = ppd
+        .get_doll(doll_id)
+        .ok_or_else(|| anyhow!("Doll {} not found.", doll_id))?;
+
+    // slots which are not combined keep their fragment in fixed,
+    // required ones without it fall back to their first candidate
+    let mut base = HashMap::new();
+
+    for slot_id in &doll.slots {
+        if slots.iter().any(|sheet_slot| sheet_slot.slot == *slot_id) {
+            continue;
+        }
+
+        let Some(slot) = ppd.get_slot(*slot_id) else {
+            continue;
+        };
+
+        let fragment_id = fixed
+            .get(slot_id)
+            .filter(|fragment_id| slot.candidates.contains(fragment_id))
+            .or_else(|| slot.required.then(|| slot.candidates.first()).flatten());
+
+        if let Some(fragment_id) = fragment_id {
+            base.insert(*slot_id, *fragment_id);
+        }
+    }
+
+    let mut options: Vec<(u32, Vec<Option<u32>>)> = vec![];
+
+    for sheet_slot in slots {
+        if !doll.slots.contains(&sheet_slot.slot) {
+            bail!(
+                "Slot {} does not belong to doll {}.",
+                sheet_slot.slot,
+                doll_id
+            );
+        }
+
+        let slot = ppd
+            .get_slot(sheet_slot.slot)
+            .ok_or_else(|| anyhow!("Slot {} not found.", sheet_slot.slot))?;
+
+        let candidates = match &sheet_slot.candidates {
+            Some(candidates) => {
+                for fragment_id in candidates {
+                    if !slot.candidates.contains(fragment_id) {
+                        bail!(
+                            "Fragment {} is not a candidate of slot {}.",
+                            fragment_id,
+                            sheet_slot.slot
+                        );
+                    }
+                }
+
+                candidates.clone()
+            }
+            None => slot.candidates.clone(),
+        };
+
+        let mut slot_options: Vec<Option<u32>> = vec![];
+
+        // an empty slot is a valid option unless the slot is required
+        if !slot.required {
+            slot_options.push(None);
+        }
+
+        slot_options.extend(candidates.into_iter().map(Some));
+
+        if slot_options.is_empty() {
+            bail!(
+                "Slot {} is required but has no candidates.",
+                sheet_slot.slot
+            );
+        }
+
+        options.push((sheet_slot.slot, slot_options));
+    }
+
+    let count = options
+        .iter()
+        .try_fold(1usize, |count, (_, slot_options)| {
+            count.checked_mul(slot_options.len())
+        })
+        .filter(|count| *count <= limit)
+        .ok_or_else(|| anyhow!("Too many combinations, the limit is {}.", limit))?;
+
+    let mut combinations = Vec::with_capacity(count);
+
+    for index in 0..count {
+        let mut slot_map = base.clone();
+        let mut rest = index;
+
+        // the last slot varies the fastest
+        for (slot_id, slot_options) in options.iter().rev() {
+            if let Some(fragment_id) = slot_options[rest % slot_options.len()] {
+                slot_map.insert(*slot_id, fragment_id);
+            }
+
+            rest /= slot_options.len();
+        }
+
+        combinations.push(slot_map);
+    }
+
+    Ok(combinations)
+}
+
+pub fn count_combinations(ppd: &PaperdollFactory, slots: &[u32]) -> Option<usize> {
+    slots.iter().try_fold(1usize, |count, slot_id| {
+        let slot = ppd.get_slot(*slot_id)?;
+
+        let options = slot.candidates.len() + if slot.required { 0 } else { 1 };
+
+        count.checked_mul(options)
+    })
+}
+
+pub fn render_sprite_sheet(
+    ppd: &PaperdollFactory,
+    doll_id: u32,
+    combinations: Vec<HashMap<u32, u32>>,
+    columns: Option<u32>,
+) -> Result<(RgbaImage, SheetIndex)> {
+    let doll = ppd
+        .get_doll(doll_id)
+        .ok_or_else(|| anyhow!("Doll {} not found.", doll_id))?;
+
+    if combinations.is_empty() {
+        bail!("No combinations to render.");
+    }
+
+    let count = combinations.len() as u32;
+
+    let columns = columns
+        .unwrap_or_else(|| (count as f64).sqrt().ceil() as u32)
+        .clamp(1, count);
+    let rows = (count + columns - 1) / columns;
+
+    let width = doll.width as u64 * columns as u64;
+    let height = doll.height as u64 * rows as u64;
+
+    if width * height > MAX_SHEET_PIXELS {
+        bail!(
+            "The sprite sheet of {}x{} pixels is too large, the limit is {} pixels.",
+            width,
+            height,
+            MAX_SHEET_PIXELS
+        );
+    }
+
+    let mut sheet = RgbaImage::new(doll.width * columns, doll.height * rows);
+
+    let mut cells = Vec::with_capacity(combinations.len());
+
+    for (index, slot_map) in combinations.into_iter().enumerate() {
+        let paperdoll = Paperdoll {
+            doll: doll_id,
+            slot_map,
+        };
+
+        let image = ppd.render_paperdoll(&paperdoll)?;
+
+        let image = RgbaImage::from_raw(image.width, image.height, image.pixels)
+            .ok_or_else(|| anyhow!("Invalid image rendered for doll {}.", doll_id))?;
+
+        let x = index as u32 % columns * doll.width;
+        let y = index as u32 / columns * doll.height;
+
+        imageops::replace(&mut sheet, &image, x as i64, y as i64);
+
+        cells.push(SheetCell {
+            x,
+            y,
+            width: doll.width,
+            height: doll.height,
+            slot_map: paperdoll.slot_map.into_iter().collect(),
+        });
+    }
+
+    let index = SheetIndex {
+        doll: doll_id,
+        columns,
+        rows,
+        cells,
+    };
+
+    Ok((sheet, index))
+}
+
+pub fn save_sprite_sheet<P>(sheet: &RgbaImage, index: &SheetIndex, path: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+
+    save_image(sheet, path)?;
+
+    fs::write(
+        path.with_extension("json"),
+        serde_json::to_string_pretty(index)?,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use paperdoll_tar::paperdoll::PaperdollFactory;
+
+    use crate::test_utils::factory_with_slots;
+
+    use super::{combinations, SheetSlot};
+
+    // a required slot and an optional slot, each with two candidates
+    fn factory() -> (PaperdollFactory, u32, u32) {
+        let (ppd, slots) = factory_with_slots(&[(true, 2), (false, 2)]);
+
+        (ppd, slots[0], slots[1])
+    }
+
+    fn sheet_slot(slot: u32) -> SheetSlot {
+        SheetSlot {
+            slot,
+            candidates: None,
+        }
+    }
+
+    #[test]
+    fn combines_every_candidate() {
+        let (ppd, required, optional) = factory();
+
+        let result = combinations(
+            &ppd,
+            0,
+            &[sheet_slot(required), sheet_slot(optional)],
+            &HashMap::new(),
+            100,
+        )
+        .unwrap();
+
+        // 2 candidates of the required slot times 2 candidates and empty of the optional one
+        assert_eq!(result.len(), 6);
+        assert!(result
+            .iter()
+            .all(|slot_map| slot_map.contains_key(&required)));
+        assert_eq!(
+            result
+                .iter()
+                .filter(|slot_map| !slot_map.contains_key(&optional))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn slots_not_combined_keep_fixed_fragment() {
+        let (ppd, required, optional) = factory();
+
+        let fragment = ppd.get_slot(optional).unwrap().candidates[1];
+
+        let result = combinations(
+            &ppd,
+            0,
+            &[sheet_slot(required)],
+            &HashMap::from([(optional, fragment)]),
+            100,
+        )
+        .unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert!(result
+            .iter()
+            .all(|slot_map| slot_map.get(&optional) == Some(&fragment)));
+    }
+
+    #[test]
+    fn required_slots_not_combined_use_first_candidate() {
+        let (ppd, required, optional) = factory();
+
+        let first = ppd.get_slot(required).unwrap().candidates[0];
+
+        let result = combinations(&ppd, 0, &[sheet_slot(optional)], &HashMap::new(), 100).unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert!(result
+            .iter()
+            .all(|slot_map| slot_map.get(&required) == Some(&first)));
+    }
+
+    #[test]
+    fn rejects_too_many_combinations() {
+        let (ppd, required, optional) = factory();
+
+        let slots = [sheet_slot(required), sheet_slot(optional)];
+
+        assert!(combinations(&ppd, 0, &slots, &HashMap::new(), 5).is_err());
+    }
+
+    #[test]
+    fn rejects_foreign_candidates() {
+        let (ppd, required, optional) = factory();
+
+        let foreign = ppd.get_slot(optional).unwrap().candidates[0];
+
+        let slots = [SheetSlot {
+            slot: required,
+            candidates: Some(vec![foreign]),
+        }];
+
+        assert!(combinations(&ppd, 0, &slots, &HashMap::new(), 100).is_err());
+    }
+}
@@ -0,0 +1,24 @@
+use paperdoll_tar::paperdoll::PaperdollFactory;
+
+// doll 0 with a slot for each (required, candidate count) pair, in the given order
+pub(crate) fn factory_with_slots(slots: &[(bool, usize)]) -> (PaperdollFactory, Vec<u32>) {
+    let mut ppd = PaperdollFactory::default();
+
+    let mut ids = vec![];
+
+    for (required, count) in slots {
+        let candidates = (0..*count).map(|_| ppd.add_fragment().unwrap()).collect();
+
+        let id = ppd.add_slot().unwrap();
+
+        let slot = ppd.get_slot_mut(id).unwrap();
+        slot.required = *required;
+        slot.candidates = candidates;
+
+        ppd.get_doll_mut(0).unwrap().slots.push(id);
+
+        ids.push(id);
+    }
+
+    (ppd, ids)
+}
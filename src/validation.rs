@@ -0,0 +1,358 @@
+use std::collections::HashSet;
+
+use paperdoll_tar::paperdoll::PaperdollFactory;
+
+use crate::common::sorted_ids;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ProblemTarget {
+    Doll(u32),
+    Fragment(u32),
+    Slot(u32),
+}
+
+// errors break the file or its rendering, warnings are informational
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ProblemSeverity {
+    Error,
+    Warning,
+}
+
+pub struct Problem {
+    pub severity: ProblemSeverity,
+    pub target: ProblemTarget,
+    pub message: String,
+}
+
+impl Problem {
+    fn error(target: ProblemTarget, message: impl Into<String>) -> Self {
+        Self {
+            severity: ProblemSeverity::Error,
+            target,
+            message: message.into(),
+        }
+    }
+
+    fn warning(target: ProblemTarget, message: impl Into<String>) -> Self {
+        Self {
+            severity: ProblemSeverity::Warning,
+            target,
+            message: message.into(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == ProblemSeverity::Error
+    }
+}
+
+pub fn validate(ppd: &PaperdollFactory) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    for doll_id in sorted_ids(ppd.dolls().map(|(id, _)| *id)) {
+        let Some(doll) = ppd.get_doll(doll_id) else {
+            continue;
+        };
+
+        let doll_name = display_name("Doll", doll_id, &doll.desc);
+
+        if doll.slots.is_empty() {
+            problems.push(Problem::warning(
+                ProblemTarget::Doll(doll_id),
+                format!("{} has no slots.", doll_name),
+            ));
+        }
+
+        // the visible area covers the doll and its background placed at the offset
+        let (mut min_x, mut min_y) = (0.0_f32, 0.0_f32);
+        let (mut max_x, mut max_y) = (doll.width as f32, doll.height as f32);
+
+        if !doll.image.is_empty() {
+            min_x = min_x.min(doll.offset.x);
+            min_y = min_y.min(doll.offset.y);
+            max_x = max_x.max(doll.offset.x + doll.image.width as f32);
+            max_y = max_y.max(doll.offset.y + doll.image.height as f32);
+        }
+
+        for slot_id in &doll.slots {
+            let Some(slot) = ppd.get_slot(*slot_id) else {
+                problems.push(Problem::error(
+                    ProblemTarget::Doll(doll_id),
+                    format!("{} refers to a missing slot {}.", doll_name, slot_id),
+                ));
+
+                continue;
+            };
+
+            let is_outside = slot.positions.iter().any(|position| {
+                position.x < min_x
+                    || position.y < min_y
+                    || position.x + slot.width as f32 > max_x
+                    || position.y + slot.height as f32 > max_y
+            });
+
+            if is_outside {
+                problems.push(Problem::warning(
+                    ProblemTarget::Slot(*slot_id),
+                    format!(
+                        "{} falls outside the bounds of {}.",
+                        display_name("Slot", *slot_id, &slot.desc),
+                        doll_name
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut used_fragments = HashSet::new();
+
+    for slot_id in sorted_ids(ppd.slots().map(|(id, _)| *id)) {
+        let Some(slot) = ppd.get_slot(slot_id) else {
+            continue;
+        };
+
+        let slot_name = display_name("Slot", slot_id, &slot.desc);
+
+        if slot.required && slot.candidates.is_empty() {
+            problems.push(Problem::error(
+                ProblemTarget::Slot(slot_id),
+                format!("{} is required but has no candidates.", slot_name),
+            ));
+        }
+
+        for fragment_id in &slot.candidates {
+            used_fragments.insert(*fragment_id);
+
+            if ppd.get_fragment(*fragment_id).is_none() {
+                problems.push(Problem::error(
+                    ProblemTarget::Slot(slot_id),
+                    format!(
+                        "{} refers to a missing fragment {}.",
+                        slot_name, fragment_id
+                    ),
+                ));
+            }
+        }
+    }
+
+    for fragment_id in sorted_ids(ppd.fragments().map(|(id, _)| *id)) {
+        let Some(fragment) = ppd.get_fragment(fragment_id) else {
+            continue;
+        };
+
+        let fragment_name = display_name("Fragment", fragment_id, &fragment.desc);
+
+        if fragment.image.is_empty() {
+            problems.push(Problem::warning(
+                ProblemTarget::Fragment(fragment_id),
+                format!("{} has an empty image.", fragment_name),
+            ));
+        }
+
+        if !used_fragments.contains(&fragment_id) {
+            problems.push(Problem::warning(
+                ProblemTarget::Fragment(fragment_id),
+                format!("{} is not used by any slot.", fragment_name),
+            ));
+        }
+    }
+
+    problems
+}
+
+fn display_name(kind: &str, id: u32, desc: &str) -> String {
+    if desc.is_empty() {
+        format!("{} {}", kind, id)
+    } else {
+        format!("{} {} \"{}\"", kind, id, desc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use paperdoll_tar::paperdoll::{ColorType, ImageData, PaperdollFactory, Point};
+
+    use super::{validate, ProblemSeverity, ProblemTarget};
+
+    fn image(width: u32, height: u32) -> ImageData {
+        ImageData {
+            width,
+            height,
+            color_type: ColorType::Rgba,
+            pixels: vec![255; (width * height * 4) as usize],
+        }
+    }
+
+    // doll 0 of 100x100 with a 10x10 slot at the origin holding one fragment
+    fn factory() -> (PaperdollFactory, u32, u32) {
+        let mut ppd = PaperdollFactory::default();
+
+        let fragment_id = ppd.add_fragment().unwrap();
+        ppd.get_fragment_mut(fragment_id).unwrap().image = image(1, 1);
+
+        let slot_id = ppd.add_slot().unwrap();
+
+        let slot = ppd.get_slot_mut(slot_id).unwrap();
+        slot.positions = vec![Point { x: 0.0, y: 0.0 }];
+        slot.width = 10;
+        slot.height = 10;
+        slot.candidates = vec![fragment_id];
+
+        let doll = ppd.get_doll_mut(0).unwrap();
+        doll.width = 100;
+        doll.height = 100;
+        doll.slots = vec![slot_id];
+
+        (ppd, slot_id, fragment_id)
+    }
+
+    fn has_problem(
+        ppd: &PaperdollFactory,
+        severity: ProblemSeverity,
+        target: ProblemTarget,
+    ) -> bool {
+        validate(ppd)
+            .iter()
+            .any(|problem| problem.severity == severity && problem.target == target)
+    }
+
+    #[test]
+    fn valid_file() {
+        let (ppd, _, _) = factory();
+
+        assert!(validate(&ppd).is_empty());
+    }
+
+    #[test]
+    fn missing_slot() {
+        let (mut ppd, _, _) = factory();
+
+        ppd.get_doll_mut(0).unwrap().slots.push(100);
+
+        assert!(has_problem(
+            &ppd,
+            ProblemSeverity::Error,
+            ProblemTarget::Doll(0)
+        ));
+    }
+
+    #[test]
+    fn missing_fragment() {
+        let (mut ppd, slot_id, _) = factory();
+
+        ppd.get_slot_mut(slot_id).unwrap().candidates.push(100);
+
+        assert!(has_problem(
+            &ppd,
+            ProblemSeverity::Error,
+            ProblemTarget::Slot(slot_id)
+        ));
+    }
+
+    #[test]
+    fn empty_required_slot() {
+        let (mut ppd, slot_id, _) = factory();
+
+        let slot = ppd.get_slot_mut(slot_id).unwrap();
+        slot.required = true;
+
+        assert!(!has_problem(
+            &ppd,
+            ProblemSeverity::Error,
+            ProblemTarget::Slot(slot_id)
+        ));
+
+        ppd.get_slot_mut(slot_id).unwrap().candidates.clear();
+
+        assert!(has_problem(
+            &ppd,
+            ProblemSeverity::Error,
+            ProblemTarget::Slot(slot_id)
+        ));
+    }
+
+    #[test]
+    fn slot_out_of_bounds() {
+        let (mut ppd, slot_id, _) = factory();
+
+        let move_slot = |ppd: &mut PaperdollFactory, x: f32, y: f32| {
+            ppd.get_slot_mut(slot_id).unwrap().positions = vec![Point { x, y }];
+        };
+
+        let is_outside = |ppd: &PaperdollFactory| {
+            has_problem(ppd, ProblemSeverity::Warning, ProblemTarget::Slot(slot_id))
+        };
+
+        move_slot(&mut ppd, 90.0, 90.0);
+        assert!(!is_outside(&ppd));
+
+        move_slot(&mut ppd, 95.0, 0.0);
+        assert!(is_outside(&ppd));
+
+        move_slot(&mut ppd, -5.0, 0.0);
+        assert!(is_outside(&ppd));
+
+        // a background of 200x100 placed 50 pixels to the left widens the visible area
+        let doll = ppd.get_doll_mut(0).unwrap();
+        doll.image = image(200, 100);
+        doll.offset.x = -50.0;
+
+        move_slot(&mut ppd, -50.0, 0.0);
+        assert!(!is_outside(&ppd));
+
+        move_slot(&mut ppd, 140.0, 90.0);
+        assert!(!is_outside(&ppd));
+
+        move_slot(&mut ppd, 145.0, 0.0);
+        assert!(is_outside(&ppd));
+
+        move_slot(&mut ppd, 0.0, 95.0);
+        assert!(is_outside(&ppd));
+    }
+
+    #[test]
+    fn doll_without_slots() {
+        let (mut ppd, _, _) = factory();
+
+        ppd.get_doll_mut(0).unwrap().slots.clear();
+
+        assert!(has_problem(
+            &ppd,
+            ProblemSeverity::Warning,
+            ProblemTarget::Doll(0)
+        ));
+    }
+
+    #[test]
+    fn fragment_with_empty_image() {
+        let (mut ppd, slot_id, _) = factory();
+
+        let fragment_id = ppd.add_fragment().unwrap();
+
+        ppd.get_slot_mut(slot_id)
+            .unwrap()
+            .candidates
+            .push(fragment_id);
+
+        let problems = validate(&ppd);
+
+        assert_eq!(problems.len(), 1);
+        assert!(!problems[0].is_error());
+        assert!(problems[0].target == ProblemTarget::Fragment(fragment_id));
+    }
+
+    #[test]
+    fn unused_fragment() {
+        let (mut ppd, _, _) = factory();
+
+        let fragment_id = ppd.add_fragment().unwrap();
+        ppd.get_fragment_mut(fragment_id).unwrap().image = image(1, 1);
+
+        let problems = validate(&ppd);
+
+        assert_eq!(problems.len(), 1);
+        assert!(!problems[0].is_error());
+        assert!(problems[0].target == ProblemTarget::Fragment(fragment_id));
+        assert!(problems[0].message.contains("not used"));
+    }
+}
@@ -1,11 +1,15 @@
 mod actions;
 mod config;
+mod export;
 mod menu;
+mod outfit;
+mod preset;
+mod random;
 mod shortcut;
 mod storage;
 mod ui;
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use eframe::{egui::Context, App, CreationContext, Frame};
 use paperdoll_tar::paperdoll::{Paperdoll, PaperdollFactory};
@@ -33,10 +37,34 @@ struct ViewerApp {
     paperdoll: Paperdoll,
 
     texture: Option<TextureData>,
+    textures_fragment: HashMap<u32, TextureData>,
 
     slot_index_map: HashMap<u32, isize>,
+    focused_slot: Option<u32>,
+    slot_filters: HashMap<u32, String>,
+
+    animation_slots: HashSet<u32>,
+    expanded_slots: HashSet<u32>,
+    locked_slots: HashSet<u32>,
+    sprite_sheet_slots: HashSet<u32>,
+
+    random_seed: Option<u64>,
+    random_seed_text: String,
+
+    preset_name: String,
+    preset_renaming: Option<(usize, String)>,
+
+    outfit_code_input: String,
+    outfit_code_error: Option<String>,
+
+    // title and text of a message shown in its own window, even without an opened file
+    message: Option<(&'static str, String)>,
 
     window_about_visible: bool,
+    window_animation_visible: bool,
+    window_export_visible: bool,
+    window_outfit_code_visible: bool,
+    window_sprite_sheet_visible: bool,
 
     has_editor_installed: bool,
 }
@@ -65,14 +93,13 @@ impl ViewerApp {
         cc: &CreationContext<'_>,
         ppd: Option<PaperdollFactory>,
         path: Option<String>,
+        doll: Option<u32>,
+        outfit: Option<String>,
     ) -> Self {
         let mut storage = Storage::default();
 
         if let Some(s) = cc.storage {
-            match storage.restore(s) {
-                Ok(()) => log::info!("Restoring app data successfully."),
-                Err(err) => log::error!("Restoring app data failed: {}", err),
-            }
+            storage.restore(s);
         }
 
         let mut config = Config::default();
@@ -85,8 +112,19 @@ impl ViewerApp {
         #[cfg(feature = "flatpak")]
         let has_editor_installed = true;
 
+        let mut actions = VecDeque::from([Action::PpdChanged(ppd), Action::AppTitleChanged(path)]);
+
+        if let Some(doll) = doll {
+            actions.push_back(Action::DollSelect(doll));
+        }
+
+        // the outfit code names its own doll, so it takes precedence over the doll
+        if let Some(outfit) = outfit {
+            actions.push_back(Action::OutfitCodeApply(outfit));
+        }
+
         Self {
-            actions: VecDeque::from([Action::PpdChanged(ppd), Action::AppTitleChanged(path)]),
+            actions,
             config,
             shortcut: Shortcut::default(),
             storage,
@@ -99,10 +137,33 @@ impl ViewerApp {
             },
 
             texture: None,
+            textures_fragment: HashMap::new(),
 
             slot_index_map: HashMap::new(),
+            focused_slot: None,
+            slot_filters: HashMap::new(),
+
+            animation_slots: HashSet::new(),
+            expanded_slots: HashSet::new(),
+            locked_slots: HashSet::new(),
+            sprite_sheet_slots: HashSet::new(),
+
+            random_seed: None,
+            random_seed_text: String::default(),
+
+            preset_name: String::default(),
+            preset_renaming: None,
+
+            outfit_code_input: String::default(),
+            outfit_code_error: None,
+
+            message: None,
 
             window_about_visible: false,
+            window_animation_visible: false,
+            window_export_visible: false,
+            window_outfit_code_visible: false,
+            window_sprite_sheet_visible: false,
 
             has_editor_installed,
         }
@@ -113,10 +174,12 @@ pub fn setup_eframe(
     cc: &CreationContext<'_>,
     ppd: Option<PaperdollFactory>,
     path: Option<String>,
+    doll: Option<u32>,
+    outfit: Option<String>,
 ) -> Box<dyn App> {
     load_fonts(&cc.egui_ctx);
 
     setup_style(&cc.egui_ctx);
 
-    Box::new(ViewerApp::new(cc, ppd, path))
+    Box::new(ViewerApp::new(cc, ppd, path, doll, outfit))
 }
@@ -5,26 +5,51 @@ use std::{
 
 use anyhow::{anyhow, Result};
 use eframe::{egui::Context, epaint::Vec2, Frame};
-use paperdoll_tar::paperdoll::PaperdollFactory;
+use paperdoll_tar::paperdoll::{Paperdoll, PaperdollFactory};
 
 use crate::{
-    common::{allocate_size_fit_in_rect, upload_image_to_texture},
-    fs::{export_texture, select_file},
+    common::{
+        allocate_size_fit_in_rect, file_name_or, upload_fragment_textures, upload_image_to_texture,
+    },
+    fs::{
+        export_animation, export_json, export_sprite_sheet, export_texture, select_file,
+        select_json,
+    },
+    sheet::{self, SheetSlot, MAX_COMBINATIONS},
 };
 
-use super::{ViewerApp, APP_TITLE};
+use super::{
+    outfit::{decode_outfit, encode_outfit},
+    preset::{export_presets, import_presets, Preset},
+    random::new_seed,
+    ViewerApp, APP_TITLE,
+};
 
 pub enum Action {
     AppQuit,
     AppTitleChanged(Option<String>),
     DollChanged,
+    DollSelect(u32),
     Export,
+    ExportAnimation,
+    ExportSpriteSheet,
     FileOpen,
     FileOpenPath(PathBuf),
     OpenEditor,
+    OutfitCodeApply(String),
+    OutfitCodeCopy,
+    PaperdollApply(Paperdoll),
     PpdChanged(Option<PaperdollFactory>),
+    PresetApply(usize),
+    PresetDelete(usize),
+    PresetExport,
+    PresetImport,
+    PresetRename(usize, String),
+    PresetSave(String),
+    Randomize(Option<u64>),
     RecentFilesClean,
     SlotFragmentChanged(u32, isize),
+    SlotLocked(u32, bool),
     TextureUpdate,
     ViewportCenter,
     ViewportFit,
@@ -32,6 +57,10 @@ pub enum Action {
     ViewportZoomReset,
     ViewportZoomTo(f32),
     WindowAboutVisible(bool),
+    WindowAnimationVisible(bool),
+    WindowExportVisible(bool),
+    WindowOutfitCodeVisible(bool),
+    WindowSpriteSheetVisible(bool),
 }
 
 impl ViewerApp {
@@ -47,22 +76,118 @@ impl ViewerApp {
                     frame.set_window_title(&title)
                 }
                 Action::DollChanged => {
+                    self.focused_slot = None;
+
+                    self.animation_slots.clear();
+                    self.locked_slots.clear();
+                    self.sprite_sheet_slots.clear();
+
                     self.actions.push_back(Action::TextureUpdate);
                 }
+                Action::DollSelect(id) => {
+                    let Some(ppd) = &self.ppd else {
+                        continue;
+                    };
+
+                    if ppd.get_doll(id).is_none() {
+                        self.show_message("Error", format!("Doll {} does not exist.", id));
+
+                        continue;
+                    }
+
+                    self.paperdoll.doll = id;
+
+                    self.actions.push_back(Action::DollChanged);
+                }
                 Action::Export => {
                     let Some(ppd) = &self.ppd else {
                         continue;
                     };
 
-                    if let Some(path) = export_texture(&format!("{}.png", ppd.meta.name)) {
-                        let image = ppd.render_paperdoll(&self.paperdoll)?;
-                        image::save_buffer(
-                            path,
-                            &image.pixels,
-                            image.width,
-                            image.height,
-                            image::ColorType::Rgba8,
+                    let options = &self.storage.export_options;
+
+                    let name = file_name_or(&ppd.meta.name, "Untitled");
+
+                    if let Some(path) =
+                        export_texture(&format!("{}.{}", name, options.format.extension()))
+                    {
+                        let image = options.apply(ppd.render_paperdoll(&self.paperdoll)?)?;
+
+                        options.save(&image, &path)?;
+
+                        self.window_export_visible = false;
+                    }
+                }
+                Action::ExportAnimation => {
+                    let Some(ppd) = &self.ppd else {
+                        continue;
+                    };
+
+                    let Some(doll) = ppd.get_doll(self.paperdoll.doll) else {
+                        continue;
+                    };
+
+                    let slots: Vec<u32> = doll
+                        .slots
+                        .iter()
+                        .filter(|id| self.animation_slots.contains(id))
+                        .copied()
+                        .collect();
+
+                    let options = &self.storage.animation_options;
+
+                    let name = file_name_or(&ppd.meta.name, "Untitled");
+
+                    if let Some(path) =
+                        export_animation(&format!("{}.{}", name, options.format.extension()))
+                    {
+                        let frames = options.render_frames(ppd, &self.paperdoll, &slots)?;
+
+                        options.save(frames, &path)?;
+
+                        self.window_animation_visible = false;
+                    }
+                }
+                Action::ExportSpriteSheet => {
+                    let Some(ppd) = &self.ppd else {
+                        continue;
+                    };
+
+                    let Some(doll) = ppd.get_doll(self.paperdoll.doll) else {
+                        continue;
+                    };
+
+                    let slots: Vec<SheetSlot> = doll
+                        .slots
+                        .iter()
+                        .filter(|id| self.sprite_sheet_slots.contains(id))
+                        .map(|id| SheetSlot {
+                            slot: *id,
+                            candidates: None,
+                        })
+                        .collect();
+
+                    let name = file_name_or(&ppd.meta.name, "Untitled");
+
+                    if let Some(path) = export_sprite_sheet(&format!("{}_sheet.png", name)) {
+                        let combinations = sheet::combinations(
+                            ppd,
+                            self.paperdoll.doll,
+                            &slots,
+                            &self.paperdoll.slot_map,
+                            MAX_COMBINATIONS,
                         )?;
+
+                        let (image, index) = sheet::render_sprite_sheet(
+                            ppd,
+                            self.paperdoll.doll,
+                            combinations,
+                            None,
+                        )?;
+
+                        sheet::save_sprite_sheet(&image, &index, path)?;
+
+                        self.window_sprite_sheet_visible = false;
                     }
                 }
                 Action::FileOpen => {
@@ -100,6 +225,74 @@ impl ViewerApp {
                             .map_err(|e| anyhow!(e))?;
                     }
                 }
+                Action::OutfitCodeApply(code) => {
+                    // the outfit code window needs an opened file, so the message window is used
+                    let Some(ppd) = &self.ppd else {
+                        self.show_message(
+                            "Error",
+                            format!(
+                                "No paperdoll file is opened to apply the outfit code {} to.",
+                                code
+                            ),
+                        );
+
+                        continue;
+                    };
+
+                    match decode_outfit(ppd, &code) {
+                        Ok(paperdoll) => {
+                            self.outfit_code_error = None;
+
+                            self.actions.push_back(Action::PaperdollApply(paperdoll));
+                        }
+                        Err(err) => {
+                            self.outfit_code_input = code;
+
+                            self.show_outfit_code_error(err.to_string());
+                        }
+                    }
+                }
+                Action::OutfitCodeCopy => {
+                    let Some(ppd) = &self.ppd else {
+                        continue;
+                    };
+
+                    if let Some(code) = encode_outfit(ppd, &self.paperdoll) {
+                        ctx.output_mut(|o| o.copied_text = code);
+                    }
+                }
+                Action::PaperdollApply(paperdoll) => {
+                    let Some(ppd) = &self.ppd else {
+                        continue;
+                    };
+
+                    let Some(doll) = ppd.get_doll(paperdoll.doll) else {
+                        continue;
+                    };
+
+                    if paperdoll.doll != self.paperdoll.doll {
+                        self.animation_slots.clear();
+                        self.locked_slots.clear();
+                        self.sprite_sheet_slots.clear();
+                    }
+
+                    for slot_id in &doll.slots {
+                        let index = paperdoll
+                            .slot_map
+                            .get(slot_id)
+                            .zip(ppd.get_slot(*slot_id))
+                            .and_then(|(fragment_id, slot)| {
+                                slot.candidates.iter().position(|id| id == fragment_id)
+                            })
+                            .map_or(-1, |index| index as isize);
+
+                        self.slot_index_map.insert(*slot_id, index);
+                    }
+
+                    self.paperdoll = paperdoll;
+
+                    self.actions.push_back(Action::TextureUpdate);
+                }
                 Action::PpdChanged(ppd) => {
                     let Some(ppd) = ppd else {
                         self.ppd = None;
@@ -110,6 +303,21 @@ impl ViewerApp {
                     self.paperdoll.doll = 0;
                     self.paperdoll.slot_map.clear();
 
+                    self.animation_slots.clear();
+                    self.locked_slots.clear();
+                    self.sprite_sheet_slots.clear();
+
+                    self.random_seed = None;
+                    self.random_seed_text.clear();
+
+                    self.expanded_slots.clear();
+                    self.slot_filters.clear();
+
+                    self.focused_slot = None;
+
+                    // thumbnails of the candidate picker
+                    self.textures_fragment = upload_fragment_textures(&ppd, ctx);
+
                     self.slot_index_map.clear();
 
                     for (id, slot) in ppd.slots() {
@@ -129,6 +337,133 @@ impl ViewerApp {
 
                     self.ppd = Some(ppd);
                 }
+                Action::PresetApply(index) => {
+                    let Some(ppd) = &self.ppd else {
+                        continue;
+                    };
+
+                    let Some(preset) = self.presets().get(index) else {
+                        continue;
+                    };
+
+                    let Some((paperdoll, dropped)) = preset.to_paperdoll(ppd) else {
+                        let text = format!(
+                            "Doll {} of preset \"{}\" no longer exists.",
+                            preset.doll, preset.name
+                        );
+
+                        self.show_message("Preset", text);
+
+                        continue;
+                    };
+
+                    if dropped > 0 {
+                        let text = format!(
+                            "{} slot(s) of preset \"{}\" no longer match the file and were skipped.",
+                            dropped, preset.name
+                        );
+
+                        self.show_message("Preset", text);
+                    }
+
+                    self.actions.push_back(Action::PaperdollApply(paperdoll));
+                }
+                Action::PresetDelete(index) => {
+                    if let Some(presets) = self.presets_mut() {
+                        if index < presets.len() {
+                            presets.remove(index);
+                        }
+                    }
+
+                    self.preset_renaming = None;
+                }
+                Action::PresetExport => {
+                    let name = self.ppd.as_ref().map_or("presets".to_owned(), |ppd| {
+                        format!("{}_presets", file_name_or(&ppd.meta.name, "Untitled"))
+                    });
+
+                    if let Some(path) = export_json(&format!("{}.json", name)) {
+                        export_presets(self.presets(), path)?;
+                    }
+                }
+                Action::PresetImport => {
+                    // presets are kept per file path, imported ones would be lost without it
+                    if self.config.file_path.is_none() {
+                        self.show_message(
+                            "Error",
+                            "Presets can only be imported into a file opened from a path."
+                                .to_owned(),
+                        );
+
+                        continue;
+                    }
+
+                    let Some(path) = select_json() else {
+                        continue;
+                    };
+
+                    let imported = import_presets(path)?;
+
+                    if let Some(presets) = self.presets_mut() {
+                        presets.extend(imported);
+                    }
+                }
+                Action::PresetRename(index, name) => {
+                    if let Some(preset) = self
+                        .presets_mut()
+                        .and_then(|presets| presets.get_mut(index))
+                    {
+                        preset.name = name;
+                    }
+
+                    self.preset_renaming = None;
+                }
+                Action::PresetSave(name) => {
+                    let preset = Preset::new(name, &self.paperdoll);
+
+                    if let Some(presets) = self.presets_mut() {
+                        presets.push(preset);
+                    }
+
+                    self.preset_name.clear();
+                }
+                Action::Randomize(seed) => {
+                    let Some(ppd) = &self.ppd else {
+                        continue;
+                    };
+
+                    let seed = seed.unwrap_or_else(new_seed);
+
+                    let indices = self.storage.random_options.randomize(
+                        ppd,
+                        self.paperdoll.doll,
+                        seed,
+                        &self.locked_slots,
+                    );
+
+                    for (slot_id, index) in indices {
+                        self.slot_index_map.insert(slot_id, index);
+
+                        let fragment_id = ppd
+                            .get_slot(slot_id)
+                            .zip(usize::try_from(index).ok())
+                            .and_then(|(slot, index)| slot.candidates.get(index));
+
+                        match fragment_id {
+                            Some(fragment_id) => {
+                                self.paperdoll.slot_map.insert(slot_id, *fragment_id);
+                            }
+                            None => {
+                                self.paperdoll.slot_map.remove(&slot_id);
+                            }
+                        }
+                    }
+
+                    self.random_seed = Some(seed);
+                    self.random_seed_text = seed.to_string();
+
+                    self.actions.push_back(Action::TextureUpdate);
+                }
                 Action::RecentFilesClean => {
                     self.storage.recent_files.clear();
                 }
@@ -149,6 +484,13 @@ impl ViewerApp {
                         self.actions.push_back(Action::TextureUpdate);
                     }
                 }
+                Action::SlotLocked(slot_id, locked) => {
+                    if locked {
+                        self.locked_slots.insert(slot_id);
+                    } else {
+                        self.locked_slots.remove(&slot_id);
+                    }
+                }
                 Action::TextureUpdate => {
                     if let Some(ppd) = &self.ppd {
                         if let Ok(image) = ppd.render_paperdoll(&self.paperdoll) {
@@ -189,12 +531,42 @@ impl ViewerApp {
                 Action::WindowAboutVisible(visible) => {
                     self.window_about_visible = visible;
                 }
+                Action::WindowAnimationVisible(visible) => {
+                    self.window_animation_visible = visible;
+                }
+                Action::WindowExportVisible(visible) => {
+                    self.window_export_visible = visible;
+                }
+                Action::WindowOutfitCodeVisible(visible) => {
+                    self.window_outfit_code_visible = visible;
+
+                    if visible {
+                        self.outfit_code_error = None;
+                    }
+                }
+                Action::WindowSpriteSheetVisible(visible) => {
+                    self.window_sprite_sheet_visible = visible;
+                }
             }
         }
 
         Ok(())
     }
 
+    fn show_message(&mut self, title: &'static str, text: String) {
+        log::warn!("{}", text);
+
+        self.message = Some((title, text));
+    }
+
+    fn show_outfit_code_error(&mut self, error: String) {
+        log::error!("{}", error);
+
+        self.outfit_code_error = Some(error);
+
+        self.window_outfit_code_visible = true;
+    }
+
     fn load_ppd_from_path<P>(&mut self, path: P) -> Result<()>
     where
         P: AsRef<Path>,
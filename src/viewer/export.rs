@@ -0,0 +1,289 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use anyhow::{anyhow, bail, Result};
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    imageops, Delay, DynamicImage, Frame, ImageFormat, Rgba, RgbaImage,
+};
+use paperdoll_tar::paperdoll::{ImageData, Paperdoll, PaperdollFactory};
+use serde::{Deserialize, Serialize};
+
+use crate::common::save_webp;
+
+pub const EXPORT_SCALES: [u32; 4] = [1, 2, 4, 8];
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AnimationFormat {
+    Apng,
+    Gif,
+}
+
+impl AnimationFormat {
+    pub const ALL: [Self; 2] = [Self::Gif, Self::Apng];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Apng => "APNG",
+            Self::Gif => "GIF",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Apng => "png",
+            Self::Gif => "gif",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimationOptions {
+    pub delay: u32,
+    pub scale: u32,
+    pub format: AnimationFormat,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            delay: 500,
+            scale: 1,
+            format: AnimationFormat::Gif,
+        }
+    }
+}
+
+impl AnimationOptions {
+    // steps through candidates of the slots one after another, other slots are kept as they are,
+    // optional slots start with a frame without any fragment
+    pub fn render_frames(
+        &self,
+        ppd: &PaperdollFactory,
+        paperdoll: &Paperdoll,
+        slots: &[u32],
+    ) -> Result<Vec<RgbaImage>> {
+        let mut frames = vec![];
+
+        for slot_id in slots {
+            let Some(slot) = ppd.get_slot(*slot_id) else {
+                continue;
+            };
+
+            let empty = (!slot.required).then_some(None);
+
+            for fragment_id in empty.into_iter().chain(slot.candidates.iter().map(Some)) {
+                let mut slot_map = paperdoll.slot_map.clone();
+
+                match fragment_id {
+                    Some(fragment_id) => slot_map.insert(*slot_id, *fragment_id),
+                    None => slot_map.remove(slot_id),
+                };
+
+                let image = ppd.render_paperdoll(&Paperdoll {
+                    doll: paperdoll.doll,
+                    slot_map,
+                })?;
+
+                let image = RgbaImage::from_raw(image.width, image.height, image.pixels)
+                    .ok_or_else(|| anyhow!("Invalid image to export."))?;
+
+                frames.push(scale_image(image, self.scale));
+            }
+        }
+
+        if frames.is_empty() {
+            bail!("No frames to export.");
+        }
+
+        Ok(frames)
+    }
+
+    pub fn save(&self, frames: Vec<RgbaImage>, path: &Path) -> Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+
+        match self.format {
+            AnimationFormat::Apng => {
+                let Some(first) = frames.first() else {
+                    return Ok(());
+                };
+
+                let mut encoder = png::Encoder::new(file, first.width(), first.height());
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_animated(frames.len() as u32, 0)?;
+                encoder.set_frame_delay(self.delay.min(u16::MAX as u32) as u16, 1000)?;
+
+                let mut writer = encoder.write_header()?;
+
+                for frame in frames {
+                    writer.write_image_data(&frame)?;
+                }
+
+                writer.finish()?;
+            }
+            AnimationFormat::Gif => {
+                let mut encoder = GifEncoder::new_with_speed(file, 10);
+                encoder.set_repeat(Repeat::Infinite)?;
+
+                let delay = Delay::from_numer_denom_ms(self.delay, 1);
+
+                encoder.encode_frames(
+                    frames
+                        .into_iter()
+                        .map(|frame| Frame::from_parts(frame, 0, 0, delay)),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 3] = [Self::Png, Self::Webp, Self::Jpeg];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "JPEG",
+            Self::Png => "PNG",
+            Self::Webp => "WebP",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+        }
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        !matches!(self, Self::Jpeg)
+    }
+
+    fn image_format(&self) -> ImageFormat {
+        match self {
+            Self::Jpeg => ImageFormat::Jpeg,
+            Self::Png => ImageFormat::Png,
+            Self::Webp => ImageFormat::WebP,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    pub scale: u32,
+    pub transparent: bool,
+    pub background: [u8; 3],
+    pub crop: bool,
+    pub format: ExportFormat,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            transparent: true,
+            background: [255, 255, 255],
+            crop: false,
+            format: ExportFormat::Png,
+        }
+    }
+}
+
+impl ExportOptions {
+    pub fn apply(&self, image: ImageData) -> Result<DynamicImage> {
+        let mut image = RgbaImage::from_raw(image.width, image.height, image.pixels)
+            .ok_or_else(|| anyhow!("Invalid image to export."))?;
+
+        if self.crop {
+            if let Some((x, y, width, height)) = opaque_bounds(&image) {
+                image = imageops::crop_imm(&image, x, y, width, height).to_image();
+            }
+        }
+
+        image = scale_image(image, self.scale);
+
+        // formats without alpha always get a background
+        if !self.transparent || !self.format.has_alpha() {
+            fill_background(&mut image, self.background);
+        }
+
+        let image = DynamicImage::ImageRgba8(image);
+
+        Ok(if self.format.has_alpha() {
+            image
+        } else {
+            DynamicImage::ImageRgb8(image.to_rgb8())
+        })
+    }
+
+    pub fn save(&self, image: &DynamicImage, path: &Path) -> Result<()> {
+        match self.format {
+            ExportFormat::Webp => save_webp(&image.to_rgba8(), path),
+            _ => {
+                image.save_with_format(path, self.format.image_format())?;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+fn fill_background(image: &mut RgbaImage, background: [u8; 3]) {
+    for pixel in image.pixels_mut() {
+        let Rgba([r, g, b, a]) = *pixel;
+
+        let blend =
+            |fg: u8, bg: u8| ((fg as u32 * a as u32 + bg as u32 * (255 - a as u32)) / 255) as u8;
+
+        *pixel = Rgba([
+            blend(r, background[0]),
+            blend(g, background[1]),
+            blend(b, background[2]),
+            255,
+        ]);
+    }
+}
+
+fn scale_image(image: RgbaImage, scale: u32) -> RgbaImage {
+    if scale <= 1 {
+        return image;
+    }
+
+    imageops::resize(
+        &image,
+        image.width() * scale,
+        image.height() * scale,
+        imageops::FilterType::Nearest,
+    )
+}
+
+fn opaque_bounds(image: &RgbaImage) -> Option<(u32, u32, u32, u32)> {
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] == 0 {
+            continue;
+        }
+
+        bounds = Some(match bounds {
+            Some((min_x, min_y, max_x, max_y)) => {
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            }
+            None => (x, y, x, y),
+        });
+    }
+
+    bounds.map(|(min_x, min_y, max_x, max_y)| (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+}
@@ -70,7 +70,20 @@ impl ViewerApp {
                 }
 
                 if ui.button("Export").clicked() {
-                    self.actions.push_back(Action::Export);
+                    self.actions.push_back(Action::WindowExportVisible(true));
+
+                    ui.close_menu();
+                }
+
+                if ui.button("Export Animation").clicked() {
+                    self.actions.push_back(Action::WindowAnimationVisible(true));
+
+                    ui.close_menu();
+                }
+
+                if ui.button("Export Sprite Sheet").clicked() {
+                    self.actions
+                        .push_back(Action::WindowSpriteSheetVisible(true));
 
                     ui.close_menu();
                 }
@@ -90,6 +103,61 @@ impl ViewerApp {
                 }
             });
 
+            ui.menu_button("Outfit", |ui| {
+                if ui
+                    .add(
+                        Button::new("Randomize")
+                            .shortcut_text(ui.ctx().format_shortcut(&self.shortcut.randomize)),
+                    )
+                    .clicked()
+                {
+                    self.actions.push_back(Action::Randomize(None));
+
+                    ui.close_menu();
+                }
+
+                ui.separator();
+
+                if ui
+                    .add(
+                        Button::new("Previous Doll")
+                            .shortcut_text(ui.ctx().format_shortcut(&self.shortcut.doll_previous)),
+                    )
+                    .clicked()
+                {
+                    self.step_doll(-1);
+
+                    ui.close_menu();
+                }
+
+                if ui
+                    .add(
+                        Button::new("Next Doll")
+                            .shortcut_text(ui.ctx().format_shortcut(&self.shortcut.doll_next)),
+                    )
+                    .clicked()
+                {
+                    self.step_doll(1);
+
+                    ui.close_menu();
+                }
+
+                ui.separator();
+
+                if ui.button("Copy Outfit Code").clicked() {
+                    self.actions.push_back(Action::OutfitCodeCopy);
+
+                    ui.close_menu();
+                }
+
+                if ui.button("Apply Outfit Code").clicked() {
+                    self.actions
+                        .push_back(Action::WindowOutfitCodeVisible(true));
+
+                    ui.close_menu();
+                }
+            });
+
             ui.menu_button("View", |ui| {
                 if ui
                     .add(
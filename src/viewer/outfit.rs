@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use paperdoll_tar::paperdoll::{Paperdoll, PaperdollFactory};
+
+const EMPTY_CANDIDATE: &'static str = "-";
+
+// `<doll id>:<candidate index of each slot>`, slots are listed in the order of the doll
+// and separated by dots, e.g. `0:2.-.1`
+pub fn encode_outfit(ppd: &PaperdollFactory, paperdoll: &Paperdoll) -> Option<String> {
+    let doll = ppd.get_doll(paperdoll.doll)?;
+
+    let indices: Vec<String> = doll
+        .slots
+        .iter()
+        .map(|slot_id| {
+            paperdoll
+                .slot_map
+                .get(slot_id)
+                .zip(ppd.get_slot(*slot_id))
+                .and_then(|(fragment_id, slot)| {
+                    slot.candidates.iter().position(|id| id == fragment_id)
+                })
+                .map_or(EMPTY_CANDIDATE.to_owned(), |index| index.to_string())
+        })
+        .collect();
+
+    Some(format!("{}:{}", paperdoll.doll, indices.join(".")))
+}
+
+pub fn decode_outfit(ppd: &PaperdollFactory, code: &str) -> Result<Paperdoll> {
+    let code = code.trim();
+
+    let (doll_id, indices) = code
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Invalid outfit code \"{}\".", code))?;
+
+    let doll_id: u32 = doll_id
+        .parse()
+        .map_err(|_| anyhow!("Invalid doll id \"{}\" in the outfit code.", doll_id))?;
+
+    let doll = ppd
+        .get_doll(doll_id)
+        .ok_or_else(|| anyhow!("Doll {} of the outfit code does not exist.", doll_id))?;
+
+    let indices: Vec<&str> = if indices.is_empty() {
+        vec![]
+    } else {
+        indices.split('.').collect()
+    };
+
+    if indices.len() != doll.slots.len() {
+        bail!(
+            "The outfit code has {} slots while doll {} has {}.",
+            indices.len(),
+            doll_id,
+            doll.slots.len()
+        );
+    }
+
+    let mut slot_map = HashMap::new();
+
+    for (slot_id, index) in doll.slots.iter().zip(indices) {
+        let slot = ppd
+            .get_slot(*slot_id)
+            .ok_or_else(|| anyhow!("Slot {} of doll {} does not exist.", slot_id, doll_id))?;
+
+        let slot_name = if slot.desc.is_empty() {
+            format!("slot {}", slot_id)
+        } else {
+            format!("slot {} \"{}\"", slot_id, slot.desc)
+        };
+
+        if index == EMPTY_CANDIDATE {
+            if slot.required {
+                bail!("{} is required but empty in the outfit code.", slot_name);
+            }
+
+            continue;
+        }
+
+        let index: usize = index
+            .parse()
+            .map_err(|_| anyhow!("Invalid candidate \"{}\" for {}.", index, slot_name))?;
+
+        let fragment_id = slot.candidates.get(index).ok_or_else(|| {
+            anyhow!(
+                "Candidate {} is out of range for {}, which has {} candidates.",
+                index,
+                slot_name,
+                slot.candidates.len()
+            )
+        })?;
+
+        slot_map.insert(*slot_id, *fragment_id);
+    }
+
+    Ok(Paperdoll {
+        doll: doll_id,
+        slot_map,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use paperdoll_tar::paperdoll::Paperdoll;
+
+    use crate::test_utils::factory_with_slots;
+
+    use super::{decode_outfit, encode_outfit};
+
+    // a required slot of two candidates and an optional slot of three
+    const SLOTS: [(bool, usize); 2] = [(true, 2), (false, 3)];
+
+    #[test]
+    fn round_trip() {
+        let (ppd, slots) = factory_with_slots(&SLOTS);
+        let (required, optional) = (slots[0], slots[1]);
+
+        let paperdoll = Paperdoll {
+            doll: 0,
+            slot_map: HashMap::from([
+                (required, ppd.get_slot(required).unwrap().candidates[1]),
+                (optional, ppd.get_slot(optional).unwrap().candidates[2]),
+            ]),
+        };
+
+        let code = encode_outfit(&ppd, &paperdoll).unwrap();
+
+        assert_eq!(code, "0:1.2");
+        assert_eq!(
+            decode_outfit(&ppd, &code).unwrap().slot_map,
+            paperdoll.slot_map
+        );
+    }
+
+    #[test]
+    fn round_trip_empty_slot() {
+        let (ppd, slots) = factory_with_slots(&SLOTS);
+        let (required, optional) = (slots[0], slots[1]);
+
+        let paperdoll = Paperdoll {
+            doll: 0,
+            slot_map: HashMap::from([(required, ppd.get_slot(required).unwrap().candidates[0])]),
+        };
+
+        let code = encode_outfit(&ppd, &paperdoll).unwrap();
+
+        assert_eq!(code, "0:0.-");
+
+        let decoded = decode_outfit(&ppd, &format!("  {} ", code)).unwrap();
+
+        assert_eq!(decoded.doll, 0);
+        assert!(!decoded.slot_map.contains_key(&optional));
+        assert_eq!(decoded.slot_map, paperdoll.slot_map);
+    }
+
+    #[test]
+    fn encode_unknown_doll() {
+        let (ppd, _) = factory_with_slots(&SLOTS);
+
+        let paperdoll = Paperdoll {
+            doll: 100,
+            slot_map: HashMap::new(),
+        };
+
+        assert!(encode_outfit(&ppd, &paperdoll).is_none());
+    }
+
+    #[test]
+    fn decode_errors() {
+        let (ppd, _) = factory_with_slots(&SLOTS);
+
+        for code in [
+            "", "0", "x:0.0", "100:0.0", "0:0", "0:0.0.0", "0:-.0", "0:a.0", "0:2.0", "0:0.3",
+        ] {
+            assert!(
+                decode_outfit(&ppd, code).is_err(),
+                "{:?} was accepted",
+                code
+            );
+        }
+    }
+}
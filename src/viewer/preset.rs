@@ -0,0 +1,163 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::Result;
+use paperdoll_tar::paperdoll::{Paperdoll, PaperdollFactory};
+use serde::{Deserialize, Serialize};
+
+use super::ViewerApp;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub doll: u32,
+    pub slot_map: HashMap<u32, u32>,
+}
+
+impl Preset {
+    pub fn new(name: impl Into<String>, paperdoll: &Paperdoll) -> Self {
+        Self {
+            name: name.into(),
+            doll: paperdoll.doll,
+            slot_map: paperdoll.slot_map.clone(),
+        }
+    }
+
+    // slots or fragments removed from the file since the preset was saved are left out
+    // and counted, required slots left empty fall back to their first candidate
+    pub fn to_paperdoll(&self, ppd: &PaperdollFactory) -> Option<(Paperdoll, usize)> {
+        let doll = ppd.get_doll(self.doll)?;
+
+        let mut slot_map = HashMap::new();
+        let mut dropped = 0;
+
+        for (slot_id, fragment_id) in &self.slot_map {
+            let is_valid = doll.slots.contains(slot_id)
+                && ppd
+                    .get_slot(*slot_id)
+                    .map_or(false, |slot| slot.candidates.contains(fragment_id));
+
+            if is_valid {
+                slot_map.insert(*slot_id, *fragment_id);
+            } else {
+                dropped += 1;
+            }
+        }
+
+        for slot_id in &doll.slots {
+            if slot_map.contains_key(slot_id) {
+                continue;
+            }
+
+            let first = ppd
+                .get_slot(*slot_id)
+                .filter(|slot| slot.required)
+                .and_then(|slot| slot.candidates.first());
+
+            if let Some(fragment_id) = first {
+                slot_map.insert(*slot_id, *fragment_id);
+            }
+        }
+
+        Some((
+            Paperdoll {
+                doll: self.doll,
+                slot_map,
+            },
+            dropped,
+        ))
+    }
+}
+
+pub fn export_presets<P>(presets: &[Preset], path: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    fs::write(path, serde_json::to_string_pretty(presets)?)?;
+
+    Ok(())
+}
+
+pub fn import_presets<P>(path: P) -> Result<Vec<Preset>>
+where
+    P: AsRef<Path>,
+{
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+impl ViewerApp {
+    // presets are kept per file, there are none for files opened without a path
+    pub(super) fn presets(&self) -> &[Preset] {
+        self.config
+            .file_path
+            .as_ref()
+            .and_then(|path| self.storage.presets.get(path))
+            .map(|presets| presets.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub(super) fn presets_mut(&mut self) -> Option<&mut Vec<Preset>> {
+        let path = self.config.file_path.clone()?;
+
+        Some(self.storage.presets.entry(path).or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::test_utils::factory_with_slots;
+
+    use super::Preset;
+
+    #[test]
+    fn stale_entries_are_dropped() {
+        let (ppd, slots) = factory_with_slots(&[(false, 2), (false, 2)]);
+
+        let fragment = ppd.get_slot(slots[0]).unwrap().candidates[1];
+
+        let preset = Preset {
+            name: "stale".to_owned(),
+            doll: 0,
+            // a removed slot and a fragment which is not a candidate of the slot
+            slot_map: HashMap::from([(slots[0], fragment), (slots[1], fragment), (100, fragment)]),
+        };
+
+        let (paperdoll, dropped) = preset.to_paperdoll(&ppd).unwrap();
+
+        assert_eq!(dropped, 2);
+        assert_eq!(paperdoll.slot_map, HashMap::from([(slots[0], fragment)]));
+    }
+
+    #[test]
+    fn required_slots_use_first_candidate() {
+        let (ppd, slots) = factory_with_slots(&[(true, 2), (false, 2)]);
+
+        let preset = Preset {
+            name: "empty".to_owned(),
+            doll: 0,
+            slot_map: HashMap::new(),
+        };
+
+        let (paperdoll, dropped) = preset.to_paperdoll(&ppd).unwrap();
+
+        assert_eq!(dropped, 0);
+        assert_eq!(
+            paperdoll.slot_map,
+            HashMap::from([(slots[0], ppd.get_slot(slots[0]).unwrap().candidates[0])])
+        );
+    }
+
+    #[test]
+    fn missing_doll() {
+        let (ppd, _) = factory_with_slots(&[]);
+
+        let preset = Preset {
+            name: "missing".to_owned(),
+            doll: 100,
+            slot_map: HashMap::new(),
+        };
+
+        assert!(preset.to_paperdoll(&ppd).is_none());
+    }
+}
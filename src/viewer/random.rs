@@ -0,0 +1,178 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use paperdoll_tar::paperdoll::PaperdollFactory;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RandomOptions {
+    // chance of an optional slot being left empty
+    pub empty_chance: f32,
+}
+
+impl Default for RandomOptions {
+    fn default() -> Self {
+        Self { empty_chance: 0.2 }
+    }
+}
+
+impl RandomOptions {
+    // picks a candidate index for every unlocked slot of the doll, -1 means empty
+    pub fn randomize(
+        &self,
+        ppd: &PaperdollFactory,
+        doll_id: u32,
+        seed: u64,
+        locked_slots: &HashSet<u32>,
+    ) -> HashMap<u32, isize> {
+        let mut rng = SplitMix64::new(seed);
+
+        let mut indices = HashMap::new();
+
+        let Some(doll) = ppd.get_doll(doll_id) else {
+            return indices;
+        };
+
+        for slot_id in &doll.slots {
+            // numbers are drawn for locked slots too so that locking a slot keeps the others
+            let empty_roll = rng.next_f32();
+            let candidate_roll = rng.next_u64();
+
+            if locked_slots.contains(slot_id) {
+                continue;
+            }
+
+            let Some(slot) = ppd.get_slot(*slot_id) else {
+                continue;
+            };
+
+            let len = slot.candidates.len() as u64;
+
+            let index = if len == 0 || (!slot.required && empty_roll < self.empty_chance) {
+                -1
+            } else {
+                (candidate_roll % len) as isize
+            };
+
+            indices.insert(*slot_id, index);
+        }
+
+        indices
+    }
+}
+
+pub fn new_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos() as u64);
+
+    SplitMix64::new(nanos).next_u64()
+}
+
+// a tiny generator whose output never changes between versions, so seeds stay reproducible
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+
+        let mut z = self.state;
+
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+
+        z ^ (z >> 31)
+    }
+
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::test_utils::factory_with_slots;
+
+    use super::{RandomOptions, SplitMix64};
+
+    // six optional slots of five candidates each
+    const SLOTS: [(bool, usize); 6] = [(false, 5); 6];
+
+    #[test]
+    fn generator_is_stable() {
+        let mut rng = SplitMix64::new(0);
+
+        assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+        assert_eq!(rng.next_u64(), 0x6E789E6AA1B965F4);
+    }
+
+    #[test]
+    fn same_seed_same_outfit() {
+        let (ppd, slots) = factory_with_slots(&SLOTS);
+
+        let options = RandomOptions::default();
+        let locked = HashSet::new();
+
+        let first = options.randomize(&ppd, 0, 42, &locked);
+
+        assert_eq!(first.len(), slots.len());
+        assert_eq!(first, options.randomize(&ppd, 0, 42, &locked));
+
+        let differs = (0..16).any(|seed| options.randomize(&ppd, 0, seed, &locked) != first);
+
+        assert!(differs);
+    }
+
+    #[test]
+    fn locking_keeps_other_slots() {
+        let (ppd, slots) = factory_with_slots(&SLOTS);
+
+        let options = RandomOptions::default();
+
+        let unlocked = options.randomize(&ppd, 0, 7, &HashSet::new());
+
+        let locked = HashSet::from([slots[1], slots[4]]);
+
+        let result = options.randomize(&ppd, 0, 7, &locked);
+
+        assert!(!result.contains_key(&slots[1]));
+        assert!(!result.contains_key(&slots[4]));
+
+        for (slot_id, index) in &result {
+            assert_eq!(unlocked.get(slot_id), Some(index));
+        }
+    }
+
+    #[test]
+    fn empty_chance_bounds() {
+        let (ppd, _) = factory_with_slots(&SLOTS);
+
+        let locked = HashSet::new();
+
+        let always = RandomOptions { empty_chance: 1.0 };
+        let never = RandomOptions { empty_chance: 0.0 };
+
+        for seed in 0..8 {
+            assert!(always
+                .randomize(&ppd, 0, seed, &locked)
+                .values()
+                .all(|index| *index == -1));
+
+            assert!(never
+                .randomize(&ppd, 0, seed, &locked)
+                .values()
+                .all(|index| (0..5).contains(index)));
+        }
+    }
+}
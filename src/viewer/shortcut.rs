@@ -2,12 +2,36 @@ use eframe::{
     egui::{Context, Key, KeyboardShortcut, Modifiers},
     epaint::vec2,
 };
+use paperdoll_tar::paperdoll::Slot;
 
-use super::{actions::Action, ViewerApp};
+use crate::common::sorted_ids;
+
+use super::{actions::Action, ui::step_candidate_index, ViewerApp};
+
+// number keys jumping to the nth candidate of the focused slot
+const CANDIDATE_KEYS: [Key; 9] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
 
 pub(super) struct Shortcut {
     pub app_quit: KeyboardShortcut,
+    pub candidate_next: KeyboardShortcut,
+    pub candidate_previous: KeyboardShortcut,
+    pub doll_next: KeyboardShortcut,
+    pub doll_previous: KeyboardShortcut,
     pub file_open: KeyboardShortcut,
+    pub randomize: KeyboardShortcut,
+    pub slot_clear: KeyboardShortcut,
+    pub slot_focus_next: KeyboardShortcut,
+    pub slot_focus_previous: KeyboardShortcut,
     pub viewport_center: KeyboardShortcut,
     pub viewport_fit: KeyboardShortcut,
     pub viewport_move_down: KeyboardShortcut,
@@ -23,7 +47,15 @@ impl Default for Shortcut {
     fn default() -> Self {
         Self {
             app_quit: KeyboardShortcut::new(Modifiers::CTRL, Key::Q),
+            candidate_next: KeyboardShortcut::new(Modifiers::ALT, Key::ArrowRight),
+            candidate_previous: KeyboardShortcut::new(Modifiers::ALT, Key::ArrowLeft),
+            doll_next: KeyboardShortcut::new(Modifiers::NONE, Key::PageDown),
+            doll_previous: KeyboardShortcut::new(Modifiers::NONE, Key::PageUp),
             file_open: KeyboardShortcut::new(Modifiers::CTRL, Key::O),
+            randomize: KeyboardShortcut::new(Modifiers::CTRL, Key::R),
+            slot_clear: KeyboardShortcut::new(Modifiers::NONE, Key::Delete),
+            slot_focus_next: KeyboardShortcut::new(Modifiers::ALT, Key::ArrowDown),
+            slot_focus_previous: KeyboardShortcut::new(Modifiers::ALT, Key::ArrowUp),
             viewport_center: KeyboardShortcut::new(Modifiers::CTRL, Key::J),
             viewport_fit: KeyboardShortcut::new(Modifiers::CTRL, Key::K),
             viewport_move_down: KeyboardShortcut::new(Modifiers::NONE, Key::ArrowDown),
@@ -39,11 +71,20 @@ impl Default for Shortcut {
 
 impl ViewerApp {
     pub(super) fn handle_shortcut(&mut self, ctx: &Context) {
+        // outfit shortcuts are left to text fields while typing
+        if !ctx.memory(|mem| mem.focus().is_some()) {
+            self.handle_outfit_shortcut(ctx);
+        }
+
         ctx.input_mut(|i| {
             if i.consume_shortcut(&self.shortcut.file_open) {
                 self.actions.push_back(Action::FileOpen);
             }
 
+            if i.consume_shortcut(&self.shortcut.randomize) {
+                self.actions.push_back(Action::Randomize(None));
+            }
+
             if i.consume_shortcut(&self.shortcut.viewport_center) {
                 self.actions.push_back(Action::ViewportCenter);
             }
@@ -87,4 +128,147 @@ impl ViewerApp {
             }
         });
     }
+
+    fn handle_outfit_shortcut(&mut self, ctx: &Context) {
+        let mut slot_focus_step = 0;
+        let mut candidate_step = 0;
+        let mut candidate_jump = None;
+        let mut is_slot_cleared = false;
+        let mut doll_step = 0;
+
+        ctx.input_mut(|i| {
+            if i.consume_shortcut(&self.shortcut.slot_focus_next) {
+                slot_focus_step += 1;
+            }
+
+            if i.consume_shortcut(&self.shortcut.slot_focus_previous) {
+                slot_focus_step -= 1;
+            }
+
+            if i.consume_shortcut(&self.shortcut.candidate_next) {
+                candidate_step += 1;
+            }
+
+            if i.consume_shortcut(&self.shortcut.candidate_previous) {
+                candidate_step -= 1;
+            }
+
+            for (index, key) in CANDIDATE_KEYS.iter().enumerate() {
+                if i.consume_key(Modifiers::NONE, *key) {
+                    candidate_jump = Some(index);
+                }
+            }
+
+            if i.consume_shortcut(&self.shortcut.slot_clear) {
+                is_slot_cleared = true;
+            }
+
+            if i.consume_shortcut(&self.shortcut.doll_next) {
+                doll_step += 1;
+            }
+
+            if i.consume_shortcut(&self.shortcut.doll_previous) {
+                doll_step -= 1;
+            }
+        });
+
+        if slot_focus_step != 0 {
+            self.move_slot_focus(slot_focus_step);
+        }
+
+        if candidate_step != 0 {
+            self.change_focused_candidate(|slot, index| {
+                Some(step_candidate_index(slot, index, candidate_step))
+            });
+        }
+
+        if let Some(candidate) = candidate_jump {
+            self.change_focused_candidate(|slot, _| {
+                (candidate < slot.candidates.len()).then_some(candidate as isize)
+            });
+        }
+
+        if is_slot_cleared {
+            self.change_focused_candidate(|slot, _| (!slot.required).then_some(-1));
+        }
+
+        if doll_step != 0 {
+            self.step_doll(doll_step);
+        }
+    }
+
+    fn move_slot_focus(&mut self, step: isize) {
+        let Some(doll) = self
+            .ppd
+            .as_ref()
+            .and_then(|ppd| ppd.get_doll(self.paperdoll.doll))
+        else {
+            return;
+        };
+
+        let len = doll.slots.len() as isize;
+
+        if len == 0 {
+            return;
+        }
+
+        let position = self
+            .focused_slot
+            .and_then(|id| doll.slots.iter().position(|slot_id| *slot_id == id));
+
+        let position = match position {
+            Some(position) => (position as isize + step).rem_euclid(len),
+            None if step > 0 => 0,
+            None => len - 1,
+        };
+
+        self.focused_slot = doll.slots.get(position as usize).copied();
+    }
+
+    // `change` returns the new candidate index of the focused slot
+    fn change_focused_candidate(&mut self, change: impl FnOnce(&Slot, isize) -> Option<isize>) {
+        let Some(slot_id) = self.focused_slot else {
+            return;
+        };
+
+        let Some(slot) = self.ppd.as_ref().and_then(|ppd| ppd.get_slot(slot_id)) else {
+            return;
+        };
+
+        let current_index = self.slot_index_map.get(&slot_id).copied().unwrap_or(-1);
+
+        let Some(index) = change(slot, current_index) else {
+            return;
+        };
+
+        if index != current_index {
+            self.slot_index_map.insert(slot_id, index);
+
+            self.actions
+                .push_back(Action::SlotFragmentChanged(slot_id, index));
+        }
+    }
+
+    pub(super) fn step_doll(&mut self, step: isize) {
+        let Some(ppd) = &self.ppd else {
+            return;
+        };
+
+        let ids = sorted_ids(ppd.dolls().map(|(id, _)| *id));
+
+        if ids.len() < 2 {
+            return;
+        }
+
+        let position = ids
+            .iter()
+            .position(|id| *id == self.paperdoll.doll)
+            .unwrap_or(0) as isize;
+
+        let position = (position + step).rem_euclid(ids.len() as isize);
+
+        self.paperdoll.doll = ids[position as usize];
+
+        self.actions.push_back(Action::DollChanged);
+    }
 }
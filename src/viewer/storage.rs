@@ -1,38 +1,83 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use anyhow::Result;
 
-use crate::fixed_vec::FixedVec;
+use crate::{common::restore_storage_value, fixed_vec::FixedVec};
+
+use super::{
+    export::{AnimationOptions, ExportOptions},
+    preset::Preset,
+    random::RandomOptions,
+};
 
 const RECENT_FILE_COUNT: usize = 5;
 
+const KEY_ANIMATION_OPTIONS: &'static str = "animation_options";
+const KEY_EXPORT_OPTIONS: &'static str = "export_options";
+const KEY_PRESETS: &'static str = "presets";
+const KEY_RANDOM_OPTIONS: &'static str = "random_options";
 const KEY_RECENT_FILES: &'static str = "recent_files";
 
 pub struct Storage {
+    pub animation_options: AnimationOptions,
+    pub export_options: ExportOptions,
+    pub presets: HashMap<PathBuf, Vec<Preset>>,
+    pub random_options: RandomOptions,
     pub recent_files: FixedVec<PathBuf>,
 }
 
 impl Default for Storage {
     fn default() -> Self {
         Self {
+            animation_options: AnimationOptions::default(),
+            export_options: ExportOptions::default(),
+            presets: HashMap::new(),
+            random_options: RandomOptions::default(),
             recent_files: FixedVec::new(RECENT_FILE_COUNT),
         }
     }
 }
 
 impl Storage {
-    pub fn restore(&mut self, storage: &dyn eframe::Storage) -> Result<()> {
-        if let Some(value) = storage.get_string(KEY_RECENT_FILES) {
-            self.recent_files = serde_json::from_str(&value)?;
+    pub fn restore(&mut self, storage: &dyn eframe::Storage) {
+        if let Some(value) = restore_storage_value(storage, KEY_ANIMATION_OPTIONS) {
+            self.animation_options = value;
+        }
+
+        if let Some(value) = restore_storage_value(storage, KEY_EXPORT_OPTIONS) {
+            self.export_options = value;
+        }
+
+        if let Some(value) = restore_storage_value(storage, KEY_PRESETS) {
+            self.presets = value;
+        }
+
+        if let Some(value) = restore_storage_value(storage, KEY_RANDOM_OPTIONS) {
+            self.random_options = value;
+        }
+
+        if let Some(value) = restore_storage_value(storage, KEY_RECENT_FILES) {
+            self.recent_files = value;
 
             self.recent_files
                 .resize(RECENT_FILE_COUNT, PathBuf::default());
         }
-
-        Ok(())
     }
 
     pub fn save(&self, storage: &mut dyn eframe::Storage) -> Result<()> {
+        storage.set_string(
+            KEY_ANIMATION_OPTIONS,
+            serde_json::to_string(&self.animation_options)?,
+        );
+        storage.set_string(
+            KEY_EXPORT_OPTIONS,
+            serde_json::to_string(&self.export_options)?,
+        );
+        storage.set_string(KEY_PRESETS, serde_json::to_string(&self.presets)?);
+        storage.set_string(
+            KEY_RANDOM_OPTIONS,
+            serde_json::to_string(&self.random_options)?,
+        );
         storage.set_string(KEY_RECENT_FILES, serde_json::to_string(&self.recent_files)?);
 
         Ok(())
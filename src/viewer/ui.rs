@@ -1,17 +1,33 @@
+use std::collections::HashMap;
+
 use eframe::{
     egui::{
-        scroll_area::ScrollBarVisibility, Button, CentralPanel, ComboBox, Context, Grid,
-        PointerButton, RichText, ScrollArea, Sense, SidePanel, TopBottomPanel, Ui, Window,
+        scroll_area::ScrollBarVisibility, Button, CentralPanel, ComboBox, Context, DragValue,
+        FontId, Grid, Key, Label, PointerButton, Response, RichText, ScrollArea, Sense, SidePanel,
+        TextEdit, TopBottomPanel, Ui, Window,
     },
     emath::Align2,
     epaint::{pos2, vec2, Color32, Rect, Stroke, Vec2},
 };
 use material_icons::{icon_to_char, Icon};
-use paperdoll_tar::paperdoll::{Doll, Slot};
+use paperdoll_tar::paperdoll::{Doll, PaperdollFactory, Slot};
+
+use crate::{
+    common::{allocate_size_fit_in_rect, determine_doll_rect, drag_move, TextureData},
+    sheet::{count_combinations, MAX_COMBINATIONS},
+};
 
-use crate::common::{determine_doll_rect, drag_move};
+use super::{
+    actions::Action,
+    export::{AnimationFormat, ExportFormat, EXPORT_SCALES},
+    outfit::encode_outfit,
+    ViewerApp,
+};
 
-use super::{actions::Action, ViewerApp};
+const CANDIDATE_PICKER_HEIGHT: f32 = 200.0;
+const CANDIDATE_PICKER_WIDTH: f32 = 200.0;
+const CANDIDATE_SEARCH_THRESHOLD: usize = 8;
+const CANDIDATE_TILE_SIZE: f32 = 44.0;
 
 impl ViewerApp {
     pub(super) fn ui(&mut self, ctx: &Context) {
@@ -20,6 +36,8 @@ impl ViewerApp {
                 self.ui_splash(ui);
             });
 
+            self.ui_message_window(ctx);
+
             return;
         }
 
@@ -43,11 +61,27 @@ impl ViewerApp {
                 self.ui_control(ui);
             });
 
+        SidePanel::left("presets")
+            .default_width(200.0)
+            .show(ctx, |ui| {
+                self.ui_presets(ui);
+            });
+
         CentralPanel::default().show(ctx, |ui| {
             self.ui_canvas(ui);
         });
 
         self.ui_about_window(ctx);
+
+        self.ui_animation_window(ctx);
+
+        self.ui_export_window(ctx);
+
+        self.ui_message_window(ctx);
+
+        self.ui_outfit_code_window(ctx);
+
+        self.ui_sprite_sheet_window(ctx);
     }
 
     fn ui_about_window(&mut self, ctx: &Context) {
@@ -131,6 +165,58 @@ impl ViewerApp {
                             }
                         }
                     });
+
+                ui.separator();
+
+                if ui
+                    .button(format!("{} Randomize", icon_to_char(Icon::Shuffle)))
+                    .on_hover_text(format!(
+                        "Pick random fragments for unlocked slots ({})",
+                        ui.ctx().format_shortcut(&self.shortcut.randomize)
+                    ))
+                    .clicked()
+                {
+                    self.actions.push_back(Action::Randomize(None));
+                }
+
+                ui.label("Seed: ");
+
+                let seed_resp = ui.add(
+                    TextEdit::singleline(&mut self.random_seed_text)
+                        .desired_width(160.0)
+                        .hint_text("Random"),
+                );
+
+                let seed = self.random_seed_text.trim().parse::<u64>().ok();
+
+                let is_seed_submitted =
+                    seed_resp.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+
+                if ui
+                    .add_enabled(seed.is_some(), Button::new("Apply"))
+                    .on_hover_text("Randomize again with this seed")
+                    .clicked()
+                    || (is_seed_submitted && seed.is_some())
+                {
+                    self.actions.push_back(Action::Randomize(seed));
+                }
+
+                ui.label("Empty chance: ");
+
+                let mut empty_chance = self.storage.random_options.empty_chance * 100.0;
+
+                if ui
+                    .add(
+                        DragValue::new(&mut empty_chance)
+                            .clamp_range(0.0..=100.0)
+                            .speed(1.0)
+                            .suffix("%"),
+                    )
+                    .on_hover_text("Chance of an optional slot being left empty")
+                    .changed()
+                {
+                    self.storage.random_options.empty_chance = empty_chance / 100.0;
+                }
             }
         });
     }
@@ -211,12 +297,73 @@ impl ViewerApp {
         let slots = &doll.slots;
 
         Grid::new("control")
-            .num_columns(2)
+            .num_columns(3)
             .max_col_width(200.0)
             .show(ui, |ui| {
                 for id in slots {
                     if let Some(slot) = ppd.get_slot(*id) {
-                        ui.strong(map_slot_title(slot));
+                        let is_locked = self.locked_slots.contains(id);
+
+                        if ui
+                            .selectable_label(
+                                is_locked,
+                                icon_to_char(if is_locked {
+                                    Icon::Lock
+                                } else {
+                                    Icon::LockOpen
+                                })
+                                .to_string(),
+                            )
+                            .on_hover_text(if is_locked {
+                                "Unlock slot"
+                            } else {
+                                "Lock slot when randomizing"
+                            })
+                            .clicked()
+                        {
+                            self.actions.push_back(Action::SlotLocked(*id, !is_locked));
+                        }
+
+                        let is_expanded = self.expanded_slots.contains(id);
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .small_button(
+                                    icon_to_char(if is_expanded {
+                                        Icon::ExpandLess
+                                    } else {
+                                        Icon::ExpandMore
+                                    })
+                                    .to_string(),
+                                )
+                                .on_hover_text(if is_expanded {
+                                    "Hide candidates"
+                                } else {
+                                    "Show candidates"
+                                })
+                                .clicked()
+                            {
+                                if is_expanded {
+                                    self.expanded_slots.remove(id);
+                                } else {
+                                    self.expanded_slots.insert(*id);
+                                }
+                            }
+
+                            let mut title = RichText::new(map_slot_title(slot)).strong();
+
+                            if self.focused_slot == Some(*id) {
+                                title = title.background_color(ui.visuals().selection.bg_fill);
+                            }
+
+                            if ui
+                                .add(Label::new(title).sense(Sense::click()))
+                                .on_hover_text("Focus slot")
+                                .clicked()
+                            {
+                                self.focused_slot = Some(*id);
+                            }
+                        });
 
                         ui.horizontal_centered(|ui| {
                             if ui
@@ -224,13 +371,7 @@ impl ViewerApp {
                                 .clicked()
                             {
                                 if let Some(current_index) = self.slot_index_map.get_mut(&id) {
-                                    *current_index -= 1;
-
-                                    if *current_index < 0 {
-                                        if slot.required || *current_index < -1 {
-                                            *current_index = slot.candidates.len() as isize - 1;
-                                        }
-                                    }
+                                    *current_index = step_candidate_index(slot, *current_index, -1);
 
                                     self.actions.push_back(Action::SlotFragmentChanged(
                                         *id,
@@ -290,11 +431,7 @@ impl ViewerApp {
                                 .clicked()
                             {
                                 if let Some(current_index) = self.slot_index_map.get_mut(&id) {
-                                    *current_index += 1;
-
-                                    if *current_index >= slot.candidates.len() as isize {
-                                        *current_index = if slot.required { 0 } else { -1 };
-                                    }
+                                    *current_index = step_candidate_index(slot, *current_index, 1);
 
                                     self.actions.push_back(Action::SlotFragmentChanged(
                                         *id,
@@ -305,8 +442,479 @@ impl ViewerApp {
                         });
 
                         ui.end_row();
+
+                        if is_expanded {
+                            ui.label("");
+                            ui.label("");
+
+                            let current_index = self.slot_index_map.get(id).copied().unwrap_or(-1);
+
+                            let filter = self.slot_filters.entry(*id).or_default();
+
+                            if let Some(index) = ui_candidate_picker(
+                                ui,
+                                ppd,
+                                slot,
+                                &self.textures_fragment,
+                                current_index,
+                                filter,
+                            ) {
+                                self.slot_index_map.insert(*id, index);
+
+                                self.actions
+                                    .push_back(Action::SlotFragmentChanged(*id, index));
+                            }
+
+                            ui.end_row();
+                        }
+                    }
+                }
+            });
+    }
+
+    fn ui_message_window(&mut self, ctx: &Context) {
+        let Some((title, text)) = &self.message else {
+            return;
+        };
+
+        let mut is_open = true;
+        let mut is_confirmed = false;
+
+        Window::new(*title)
+            .pivot(Align2::CENTER_CENTER)
+            .default_pos(ctx.screen_rect().center())
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut is_open)
+            .show(ctx, |ui| {
+                ui.label(text.as_str());
+
+                ui.vertical_centered(|ui| {
+                    if ui.button("OK").clicked() {
+                        is_confirmed = true;
+                    }
+                });
+            });
+
+        if !is_open || is_confirmed {
+            self.message = None;
+        }
+    }
+
+    fn ui_outfit_code_window(&mut self, ctx: &Context) {
+        if !self.window_outfit_code_visible {
+            return;
+        }
+
+        let Some(ppd) = &self.ppd else {
+            return;
+        };
+
+        Window::new("Outfit Code")
+            .pivot(Align2::CENTER_CENTER)
+            .default_pos(ctx.screen_rect().center())
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut self.window_outfit_code_visible)
+            .show(ctx, |ui| {
+                Grid::new("outfit_code").num_columns(2).show(ui, |ui| {
+                    ui.label("Current:");
+                    ui.horizontal(|ui| {
+                        let code = encode_outfit(ppd, &self.paperdoll).unwrap_or_default();
+
+                        ui.monospace(&code);
+
+                        if ui
+                            .small_button(icon_to_char(Icon::ContentCopy).to_string())
+                            .on_hover_text("Copy outfit code")
+                            .clicked()
+                        {
+                            self.actions.push_back(Action::OutfitCodeCopy);
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Apply:");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            TextEdit::singleline(&mut self.outfit_code_input)
+                                .desired_width(200.0)
+                                .hint_text("e.g. 0:2.-.1"),
+                        );
+
+                        if ui
+                            .add_enabled(
+                                !self.outfit_code_input.trim().is_empty(),
+                                Button::new("Apply"),
+                            )
+                            .clicked()
+                        {
+                            self.actions.push_back(Action::OutfitCodeApply(
+                                self.outfit_code_input.trim().to_owned(),
+                            ));
+                        }
+                    });
+                    ui.end_row();
+                });
+
+                if let Some(error) = &self.outfit_code_error {
+                    ui.separator();
+
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            });
+    }
+
+    fn ui_presets(&mut self, ui: &mut Ui) {
+        ui.heading("Presets");
+
+        if self.config.file_path.is_none() {
+            ui.label(RichText::new("Presets are only kept for opened files.").weak());
+
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.preset_name)
+                    .desired_width(120.0)
+                    .hint_text("Preset name"),
+            );
+
+            let name = self.preset_name.trim();
+
+            if ui
+                .add_enabled(!name.is_empty(), Button::new("Save"))
+                .on_hover_text("Save the current outfit as a preset")
+                .clicked()
+            {
+                self.actions.push_back(Action::PresetSave(name.to_owned()));
+            }
+        });
+
+        ui.separator();
+
+        let presets: Vec<String> = self
+            .presets()
+            .iter()
+            .map(|preset| preset.name.clone())
+            .collect();
+
+        if presets.is_empty() {
+            ui.label("No presets saved.");
+        }
+
+        ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .max_height(ui.available_height() - 40.0)
+            .show(ui, |ui| {
+                for (index, name) in presets.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if let Some((renaming_index, renaming_name)) = &mut self.preset_renaming {
+                            if *renaming_index == index {
+                                let resp = ui
+                                    .add(TextEdit::singleline(renaming_name).desired_width(120.0));
+
+                                if resp.lost_focus() {
+                                    let name = renaming_name.trim().to_owned();
+
+                                    if name.is_empty() {
+                                        self.preset_renaming = None;
+                                    } else {
+                                        self.actions.push_back(Action::PresetRename(index, name));
+                                    }
+                                } else {
+                                    resp.request_focus();
+                                }
+
+                                return;
+                            }
+                        }
+
+                        if ui.button(name).on_hover_text("Apply preset").clicked() {
+                            self.actions.push_back(Action::PresetApply(index));
+                        }
+
+                        if ui
+                            .small_button(icon_to_char(Icon::Edit).to_string())
+                            .on_hover_text("Rename preset")
+                            .clicked()
+                        {
+                            self.preset_renaming = Some((index, name.clone()));
+                        }
+
+                        if ui
+                            .small_button(icon_to_char(Icon::Delete).to_string())
+                            .on_hover_text("Delete preset")
+                            .clicked()
+                        {
+                            self.actions.push_back(Action::PresetDelete(index));
+                        }
+                    });
+                }
+            });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            if ui.button("Import").clicked() {
+                self.actions.push_back(Action::PresetImport);
+            }
+
+            if ui
+                .add_enabled(!presets.is_empty(), Button::new("Export"))
+                .clicked()
+            {
+                self.actions.push_back(Action::PresetExport);
+            }
+        });
+    }
+
+    fn ui_animation_window(&mut self, ctx: &Context) {
+        if !self.window_animation_visible {
+            return;
+        }
+
+        let Some(ppd) = &self.ppd else {
+            return;
+        };
+
+        let Some(doll) = ppd.get_doll(self.paperdoll.doll) else {
+            return;
+        };
+
+        let options = &mut self.storage.animation_options;
+
+        Window::new("Export Animation")
+            .pivot(Align2::CENTER_CENTER)
+            .default_pos(ctx.screen_rect().center())
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut self.window_animation_visible)
+            .show(ctx, |ui| {
+                ui.label("Slots to cycle through:");
+
+                for id in &doll.slots {
+                    let Some(slot) = ppd.get_slot(*id) else {
+                        continue;
+                    };
+
+                    let mut checked = self.animation_slots.contains(id);
+
+                    if ui.checkbox(&mut checked, map_slot_title(slot)).changed() {
+                        if checked {
+                            self.animation_slots.insert(*id);
+                        } else {
+                            self.animation_slots.remove(id);
+                        }
+                    }
+                }
+
+                ui.separator();
+
+                Grid::new("animation").num_columns(2).show(ui, |ui| {
+                    ui.label("Frame Delay:");
+
+                    ui.add(
+                        DragValue::new(&mut options.delay)
+                            .clamp_range(10..=10000)
+                            .suffix(" ms"),
+                    );
+
+                    ui.end_row();
+
+                    ui.label("Scale:");
+
+                    ui.horizontal(|ui| {
+                        for scale in EXPORT_SCALES {
+                            ui.radio_value(&mut options.scale, scale, format!("{}x", scale));
+                        }
+                    });
+
+                    ui.end_row();
+
+                    ui.label("Format:");
+
+                    ui.horizontal(|ui| {
+                        for format in AnimationFormat::ALL {
+                            ui.radio_value(&mut options.format, format, format.description());
+                        }
+                    });
+
+                    ui.end_row();
+                });
+
+                let frame_count: usize = doll
+                    .slots
+                    .iter()
+                    .filter(|id| self.animation_slots.contains(id))
+                    .filter_map(|id| ppd.get_slot(*id))
+                    .map(|slot| slot.candidates.len() + if slot.required { 0 } else { 1 })
+                    .sum();
+
+                ui.label(format!("{} frames", frame_count));
+
+                ui.separator();
+
+                if ui
+                    .add_enabled(frame_count > 0, Button::new("Export"))
+                    .clicked()
+                {
+                    self.actions.push_back(Action::ExportAnimation);
+                }
+            });
+    }
+
+    fn ui_export_window(&mut self, ctx: &Context) {
+        if !self.window_export_visible {
+            return;
+        }
+
+        let options = &mut self.storage.export_options;
+
+        Window::new("Export")
+            .pivot(Align2::CENTER_CENTER)
+            .default_pos(ctx.screen_rect().center())
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut self.window_export_visible)
+            .show(ctx, |ui| {
+                Grid::new("export").num_columns(2).show(ui, |ui| {
+                    ui.label("Scale:");
+
+                    ui.horizontal(|ui| {
+                        for scale in EXPORT_SCALES {
+                            ui.radio_value(&mut options.scale, scale, format!("{}x", scale));
+                        }
+                    });
+
+                    ui.end_row();
+
+                    ui.label("Background:");
+
+                    ui.horizontal(|ui| {
+                        ui.add_enabled_ui(options.format.has_alpha(), |ui| {
+                            ui.radio_value(&mut options.transparent, true, "Transparent");
+                        });
+
+                        ui.radio_value(&mut options.transparent, false, "Solid");
+
+                        ui.color_edit_button_srgb(&mut options.background);
+                    });
+
+                    ui.end_row();
+
+                    ui.label("Size:");
+
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut options.crop, false, "Doll size");
+                        ui.radio_value(&mut options.crop, true, "Crop to content");
+                    });
+
+                    ui.end_row();
+
+                    ui.label("Format:");
+
+                    ui.horizontal(|ui| {
+                        for format in ExportFormat::ALL {
+                            ui.radio_value(&mut options.format, format, format.description());
+                        }
+                    });
+
+                    ui.end_row();
+                });
+
+                if !options.format.has_alpha() && options.transparent {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!(
+                            "{} has no transparency, the background color is used.",
+                            options.format.description()
+                        ),
+                    );
+                }
+
+                ui.separator();
+
+                if ui.button("Export").clicked() {
+                    self.actions.push_back(Action::Export);
+                }
+            });
+    }
+
+    fn ui_sprite_sheet_window(&mut self, ctx: &Context) {
+        if !self.window_sprite_sheet_visible {
+            return;
+        }
+
+        let Some(ppd) = &self.ppd else {
+            return;
+        };
+
+        let Some(doll) = ppd.get_doll(self.paperdoll.doll) else {
+            return;
+        };
+
+        Window::new("Export Sprite Sheet")
+            .pivot(Align2::CENTER_CENTER)
+            .default_pos(ctx.screen_rect().center())
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut self.window_sprite_sheet_visible)
+            .show(ctx, |ui| {
+                ui.label("Slots to combine:");
+
+                for id in &doll.slots {
+                    let Some(slot) = ppd.get_slot(*id) else {
+                        continue;
+                    };
+
+                    let mut checked = self.sprite_sheet_slots.contains(id);
+
+                    if ui.checkbox(&mut checked, map_slot_title(slot)).changed() {
+                        if checked {
+                            self.sprite_sheet_slots.insert(*id);
+                        } else {
+                            self.sprite_sheet_slots.remove(id);
+                        }
                     }
                 }
+
+                ui.separator();
+
+                let slots: Vec<u32> = doll
+                    .slots
+                    .iter()
+                    .filter(|id| self.sprite_sheet_slots.contains(id))
+                    .copied()
+                    .collect();
+
+                let count = count_combinations(ppd, &slots);
+
+                let is_valid = match count {
+                    Some(count) if count > 0 && count <= MAX_COMBINATIONS => {
+                        ui.label(format!("{} combinations", count));
+
+                        true
+                    }
+                    Some(0) => {
+                        ui.colored_label(ui.visuals().warn_fg_color, "No combinations");
+
+                        false
+                    }
+                    _ => {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!("Too many combinations, the limit is {}", MAX_COMBINATIONS),
+                        );
+
+                        false
+                    }
+                };
+
+                if ui.add_enabled(is_valid, Button::new("Export")).clicked() {
+                    self.actions.push_back(Action::ExportSpriteSheet);
+                }
             });
     }
 
@@ -409,11 +1017,132 @@ impl ViewerApp {
 
                 ui.strong("Shift + Scroll");
                 ui.label("to move horizontally");
+
+                ui.strong("Alt + Arrow Keys");
+                ui.label("or");
+                ui.strong("1-9");
+                ui.label("to browse the outfit");
             });
         });
     }
 }
 
+// returns the candidate index of the clicked tile, -1 for the empty tile
+fn ui_candidate_picker(
+    ui: &mut Ui,
+    ppd: &PaperdollFactory,
+    slot: &Slot,
+    textures: &HashMap<u32, TextureData>,
+    current_index: isize,
+    filter: &mut String,
+) -> Option<isize> {
+    let mut clicked = None;
+
+    ui.vertical(|ui| {
+        if slot.candidates.len() > CANDIDATE_SEARCH_THRESHOLD {
+            ui.add(
+                TextEdit::singleline(filter)
+                    .desired_width(CANDIDATE_PICKER_WIDTH)
+                    .hint_text("Search candidates"),
+            );
+        }
+
+        let keyword = filter.trim().to_lowercase();
+
+        ScrollArea::vertical()
+            .id_source(("candidates", slot.id()))
+            .max_height(CANDIDATE_PICKER_HEIGHT)
+            .show(ui, |ui| {
+                ui.set_max_width(CANDIDATE_PICKER_WIDTH);
+
+                ui.horizontal_wrapped(|ui| {
+                    if !slot.required {
+                        let resp =
+                            ui_candidate_tile(ui, None, current_index == -1).on_hover_text("Empty");
+
+                        if resp.clicked() {
+                            clicked = Some(-1);
+                        }
+                    }
+
+                    for (index, fragment_id) in slot.candidates.iter().enumerate() {
+                        let Some(fragment) = ppd.get_fragment(*fragment_id) else {
+                            continue;
+                        };
+
+                        if !keyword.is_empty() && !fragment.desc.to_lowercase().contains(&keyword) {
+                            continue;
+                        }
+
+                        let hover_text = if fragment.desc.is_empty() {
+                            format!("Unnamed Fragment - {}", fragment_id)
+                        } else {
+                            fragment.desc.clone()
+                        };
+
+                        let resp = ui_candidate_tile(
+                            ui,
+                            Some(textures.get(fragment_id)),
+                            current_index == index as isize,
+                        )
+                        .on_hover_text(hover_text);
+
+                        if resp.clicked() {
+                            clicked = Some(index as isize);
+                        }
+                    }
+                });
+            });
+    });
+
+    clicked
+}
+
+// `None` is the empty tile, `Some(None)` is a fragment without image
+fn ui_candidate_tile(
+    ui: &mut Ui,
+    texture: Option<Option<&TextureData>>,
+    actived: bool,
+) -> Response {
+    let (rect, resp) = ui.allocate_exact_size(Vec2::splat(CANDIDATE_TILE_SIZE), Sense::click());
+
+    let visuals = ui.style().interact_selectable(&resp, actived);
+
+    ui.painter()
+        .rect(rect, 2.0, visuals.weak_bg_fill, visuals.bg_stroke);
+
+    let painter = ui.painter_at(rect.shrink(2.0));
+
+    if let Some(Some(texture)) = texture {
+        painter.image(
+            texture.texture.id(),
+            allocate_size_fit_in_rect(
+                texture.width as f32,
+                texture.height as f32,
+                &rect.shrink(4.0),
+            ),
+            Rect::from([pos2(0.0, 0.0), pos2(1.0, 1.0)]),
+            Color32::WHITE,
+        );
+    } else {
+        let icon = if texture.is_none() {
+            Icon::Block
+        } else {
+            Icon::BrokenImage
+        };
+
+        painter.text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            icon_to_char(icon),
+            FontId::proportional(CANDIDATE_TILE_SIZE * 0.5),
+            visuals.text_color(),
+        );
+    }
+
+    resp
+}
+
 fn map_doll_title(doll: &Doll) -> String {
     doll.desc
         .is_empty()
@@ -427,3 +1156,83 @@ fn map_slot_title(slot: &Slot) -> String {
         .then_some(format!("Unnamed Slot - {}", slot.id()))
         .map_or(slot.desc.clone(), |s| s)
 }
+
+// steps through the candidates of a slot, wrapping around to the empty candidate -1 for
+// optional slots
+pub(super) fn step_candidate_index(slot: &Slot, index: isize, step: isize) -> isize {
+    let first = if slot.required { 0 } else { -1 };
+
+    let count = slot.candidates.len() as isize - first;
+
+    if count <= 0 {
+        return -1;
+    }
+
+    // an empty required slot sits between its last and first candidate
+    let position = if index >= first {
+        index - first
+    } else if step > 0 {
+        -1
+    } else {
+        count
+    };
+
+    (position + step).rem_euclid(count) + first
+}
+
+#[cfg(test)]
+mod tests {
+    use paperdoll_tar::paperdoll::PaperdollFactory;
+
+    use crate::test_utils::factory_with_slots;
+
+    use super::step_candidate_index;
+
+    // a factory holding a single slot with the given number of candidates
+    fn factory(required: bool, count: usize) -> (PaperdollFactory, u32) {
+        let (ppd, slots) = factory_with_slots(&[(required, count)]);
+
+        (ppd, slots[0])
+    }
+
+    #[test]
+    fn required_slot_wraps_around() {
+        let (ppd, id) = factory(true, 3);
+        let slot = ppd.get_slot(id).unwrap();
+
+        assert_eq!(step_candidate_index(slot, 0, 1), 1);
+        assert_eq!(step_candidate_index(slot, 2, 1), 0);
+        assert_eq!(step_candidate_index(slot, 0, -1), 2);
+        assert_eq!(step_candidate_index(slot, 1, 5), 0);
+    }
+
+    #[test]
+    fn optional_slot_passes_through_empty() {
+        let (ppd, id) = factory(false, 2);
+        let slot = ppd.get_slot(id).unwrap();
+
+        assert_eq!(step_candidate_index(slot, 1, 1), -1);
+        assert_eq!(step_candidate_index(slot, -1, 1), 0);
+        assert_eq!(step_candidate_index(slot, -1, -1), 1);
+        assert_eq!(step_candidate_index(slot, 0, -1), -1);
+    }
+
+    #[test]
+    fn required_slot_leaves_empty_index() {
+        let (ppd, id) = factory(true, 2);
+        let slot = ppd.get_slot(id).unwrap();
+
+        assert_eq!(step_candidate_index(slot, -1, 1), 0);
+        assert_eq!(step_candidate_index(slot, -1, -1), 1);
+        assert_eq!(step_candidate_index(slot, -1, 2), 1);
+    }
+
+    #[test]
+    fn slot_without_candidates() {
+        for required in [true, false] {
+            let (ppd, id) = factory(required, 0);
+
+            assert_eq!(step_candidate_index(ppd.get_slot(id).unwrap(), 0, 1), -1);
+        }
+    }
+}